@@ -1,89 +1,315 @@
+use bevy::audio::Volume;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::time::Duration;
 
+use crate::settings::Settings;
 use crate::GameState;
 use crate::PuzzleState;
 use crate::SoundEvent;
 use crate::SoundsEnum;
 
+// how long a song takes to fade in, and the one it replaces to fade out, unless the
+// SoundEvent says otherwise
+const MUSIC_FADE: Duration = Duration::from_millis(1500);
+// how loud the music is while a sound effect plays over it
+const DUCKED_VOLUME: f32 = 0.35;
+// how long the music takes to go down to (or come back up from) the ducked volume
+const DUCK_TIME: f32 = 0.25;
+
+/// The music and the sound effects are mixed separately, each at its own volume from the
+/// settings
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum AudioBus {
+    Music,
+    Effects,
+}
+
+/// Everything about how a sound is played - added with `add_sound` when the app is built
+#[derive(Debug, Clone)]
+pub struct SoundDefinition {
+    pub path: &'static str,
+    pub bus: AudioBus,
+    // starts again from the top when it gets to the end, until it is faded out
+    pub looping: bool,
+    // on top of the bus volume
+    pub volume: f32,
+}
+
+impl SoundDefinition {
+    pub const fn song(path: &'static str) -> Self {
+        SoundDefinition {
+            path,
+            bus: AudioBus::Music,
+            looping: false,
+            volume: 1.0,
+        }
+    }
+
+    pub const fn effect(path: &'static str) -> Self {
+        SoundDefinition {
+            path,
+            bus: AudioBus::Effects,
+            looping: false,
+            volume: 1.0,
+        }
+    }
+
+    pub const fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+}
+
+/// Every sound that can be played, by the SoundsEnum a SoundEvent asks for
+#[derive(Resource, Default)]
+pub struct SoundRegistry {
+    sounds: HashMap<SoundsEnum, SoundDefinition>,
+}
+
+impl SoundRegistry {
+    pub fn get(&self, sound: SoundsEnum) -> Option<&SoundDefinition> {
+        self.sounds.get(&sound)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&SoundsEnum, &SoundDefinition)> {
+        self.sounds.iter()
+    }
+}
+
+pub trait AddSound {
+    fn add_sound(&mut self, sound: SoundsEnum, definition: SoundDefinition) -> &mut Self;
+}
+
+/// The title music and the sound effects are added by the AudioPlugin - each round adds its own song
+impl AddSound for App {
+    fn add_sound(&mut self, sound: SoundsEnum, definition: SoundDefinition) -> &mut Self {
+        self.init_resource::<SoundRegistry>();
+
+        let mut registry = self.world.resource_mut::<SoundRegistry>();
+        if registry.get(sound).is_some() {
+            panic!("the sound {:?} has already been added", sound);
+        }
+        registry.sounds.insert(sound, definition);
+        self
+    }
+}
+
+#[derive(Resource)]
+struct SoundHandles(HashMap<SoundsEnum, Handle<AudioSource>>);
+
+/// A sound that is playing, and where it is up to fading in or out
+#[derive(Component)]
+struct Mixed {
+    bus: AudioBus,
+    // from the definition and the SoundEvent, on top of the bus volume
+    volume: f32,
+    fade_in: Timer,
+    // on its way out - it is stopped when this runs out
+    fade_out: Option<Timer>,
+}
+
+impl Mixed {
+    fn level(&self) -> f32 {
+        let fade_out = self
+            .fade_out
+            .as_ref()
+            .map_or(1.0, |fade_out| fade_out.percent_left());
+        self.volume * self.fade_in.percent() * fade_out
+    }
+
+    fn start_fade_out(&mut self, fade: Duration) {
+        // already going - don't start again from the top
+        if self.fade_out.is_none() {
+            self.fade_out = Some(Timer::new(fade, TimerMode::Once));
+        }
+    }
+}
+
+/// How far the music is ducked - 1.0 is not at all
 #[derive(Resource)]
-struct SoundResource {
-    handle_vector: Vec<Handle<AudioSource>>,
+struct Ducking(f32);
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Ducking(1.0)
+    }
 }
 
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::TitleScreen), load_audio)
-            .add_systems(OnEnter(PuzzleState::GetReady), stop_all_sounds)
-            .add_systems(Update, play_sound);
+        app.add_sound(
+            SoundsEnum::TitleScreen,
+            SoundDefinition::song("snowglobe/sounds/title screen.ogg"),
+        )
+        .add_sound(
+            SoundsEnum::HoHoHo,
+            SoundDefinition::effect("snowglobe/sounds/ho ho ho.ogg"),
+        )
+        .add_sound(
+            SoundsEnum::Success,
+            SoundDefinition::effect("snowglobe/sounds/success.ogg"),
+        )
+        .add_sound(
+            SoundsEnum::Failure,
+            SoundDefinition::effect("snowglobe/sounds/failure.ogg"),
+        )
+        .add_sound(
+            SoundsEnum::MerryChristmas,
+            SoundDefinition::song("snowglobe/sounds/merry christmas.ogg"),
+        )
+        .init_resource::<Ducking>()
+        .add_systems(Startup, load_audio)
+        .add_systems(OnEnter(PuzzleState::GetReady), fade_out_music)
+        // so the title music doesn't double up when coming back from stage select
+        .add_systems(OnExit(GameState::StageSelect), fade_out_music)
+        .add_systems(OnExit(GameState::Leaderboard), fade_out_music)
+        .add_systems(OnExit(GameState::ShowScore), fade_out_music)
+        // a round restarted or abandoned from the pause menu
+        .add_systems(OnExit(GameState::Puzzle), fade_out_music)
+        .add_systems(Update, (play_sound, duck_music, mix).chain());
     }
 }
 
-fn load_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // beware!  this must match the order of SoundEnum
-    commands.insert_resource(SoundResource {
-        handle_vector: vec![
-            asset_server.load("snowglobe/sounds/title screen.ogg"),
-            asset_server.load("snowglobe/sounds/ho ho ho.ogg"),
-            asset_server.load("snowglobe/sounds/success.ogg"),
-            asset_server.load("snowglobe/sounds/failure.ogg"),
-            asset_server.load("snowglobe/sounds/turkey mistletoe.ogg"),
-            asset_server.load("snowglobe/sounds/periodic table.ogg"),
-            asset_server.load("snowglobe/sounds/twelve days.ogg"),
-            asset_server.load("snowglobe/sounds/reindeer.ogg"),
-            asset_server.load("snowglobe/sounds/waltz.ogg"),
-            asset_server.load("snowglobe/sounds/grocery.ogg"),
-            asset_server.load("snowglobe/sounds/family.ogg"),
-            asset_server.load("snowglobe/sounds/cranberries.ogg"),
-            asset_server.load("snowglobe/sounds/merry christmas.ogg"),
-        ],
-    });
+fn load_audio(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    sound_registry: Res<SoundRegistry>,
+) {
+    commands.insert_resource(SoundHandles(
+        sound_registry
+            .sounds
+            .iter()
+            .map(|(sound, definition)| (*sound, asset_server.load(definition.path)))
+            .collect(),
+    ));
 }
 
 fn play_sound(
     mut commands: Commands,
-    sound_resources: Res<SoundResource>,
+    sound_registry: Res<SoundRegistry>,
+    sound_handles: Res<SoundHandles>,
+    settings: Res<Settings>,
+    ducking: Res<Ducking>,
     mut sound_events: EventReader<SoundEvent>,
+    mut playing_query: Query<&mut Mixed>,
 ) {
     for sound_event in sound_events.iter() {
-        commands.spawn(AudioBundle {
-            source: match sound_event.0 {
-                SoundsEnum::TitleScreen => sound_resources.handle_vector[0].clone(),
-                SoundsEnum::HoHoHo => sound_resources.handle_vector[1].clone(),
-                SoundsEnum::Success => sound_resources.handle_vector[2].clone(),
-                SoundsEnum::Failure => sound_resources.handle_vector[3].clone(),
-                SoundsEnum::TurkeyMistletoeSong => sound_resources.handle_vector[4].clone(),
-                SoundsEnum::PeriodicTableSong => sound_resources.handle_vector[5].clone(),
-                SoundsEnum::TwelveDaysSong => sound_resources.handle_vector[6].clone(),
-                SoundsEnum::ReindeerSong => sound_resources.handle_vector[7].clone(),
-                SoundsEnum::WaltzSong => sound_resources.handle_vector[8].clone(),
-                SoundsEnum::GrocerySong => sound_resources.handle_vector[9].clone(),
-                SoundsEnum::FamilyTreeSong => sound_resources.handle_vector[10].clone(),
-                SoundsEnum::CranberriesSong => sound_resources.handle_vector[11].clone(),
-                SoundsEnum::MerryChristmas => sound_resources.handle_vector[12].clone(),
-            },
-            settings: match sound_event.0 {
-                SoundsEnum::TitleScreen
-                | SoundsEnum::HoHoHo
-                | SoundsEnum::Success
-                | SoundsEnum::Failure
-                | SoundsEnum::TurkeyMistletoeSong
-                | SoundsEnum::WaltzSong
-                | SoundsEnum::GrocerySong
-                | SoundsEnum::CranberriesSong
-                | SoundsEnum::MerryChristmas
-                | SoundsEnum::PeriodicTableSong => PlaybackSettings::REMOVE,
-                SoundsEnum::TwelveDaysSong
-                | SoundsEnum::ReindeerSong
-                | SoundsEnum::FamilyTreeSong => PlaybackSettings::LOOP,
-            },
+        let (Some(definition), Some(handle)) = (
+            sound_registry.get(sound_event.sound),
+            sound_handles.0.get(&sound_event.sound),
+        ) else {
+            warn!("{:?} has not been added as a sound", sound_event.sound);
+            continue;
+        };
+
+        let bus = definition.bus;
+        let fade = sound_event.fade.unwrap_or(match bus {
+            AudioBus::Music => MUSIC_FADE,
+            AudioBus::Effects => Duration::ZERO,
         });
+
+        // one song at a time - whatever was playing fades out as the new one fades in
+        if bus == AudioBus::Music {
+            for mut mixed in playing_query.iter_mut() {
+                if mixed.bus == AudioBus::Music {
+                    mixed.start_fade_out(fade);
+                }
+            }
+        }
+
+        let mixed = Mixed {
+            bus,
+            volume: definition.volume * sound_event.volume.unwrap_or(1.0),
+            fade_in: Timer::new(fade, TimerMode::Once),
+            fade_out: None,
+        };
+        let volume = bus_volume(bus, &settings, &ducking) * mixed.level();
+
+        commands.spawn((
+            AudioBundle {
+                source: handle.clone(),
+                settings: if definition.looping {
+                    PlaybackSettings::LOOP
+                } else {
+                    PlaybackSettings::DESPAWN
+                }
+                .with_volume(Volume::new_relative(volume)),
+            },
+            bus,
+            mixed,
+        ));
+    }
+}
+
+// the settings' volume for a bus, with the music turned down while it is ducked
+fn bus_volume(bus: AudioBus, settings: &Settings, ducking: &Ducking) -> f32 {
+    match bus {
+        AudioBus::Music => settings.volume(bus) * ducking.0,
+        AudioBus::Effects => settings.volume(bus),
+    }
+}
+
+// the music goes quiet while a sound effect plays, so the effect can be heard over it
+fn duck_music(
+    time: Res<Time>,
+    mut ducking: ResMut<Ducking>,
+    effects_query: Query<(&AudioSink, &AudioBus)>,
+) {
+    let effect_playing = effects_query.iter().any(|(audio_sink, bus)| {
+        *bus == AudioBus::Effects && !audio_sink.empty() && !audio_sink.is_paused()
+    });
+    let target = if effect_playing { DUCKED_VOLUME } else { 1.0 };
+
+    let step = (1.0 - DUCKED_VOLUME) * time.delta_seconds() / DUCK_TIME;
+    let ducked = if ducking.0 < target {
+        (ducking.0 + step).min(target)
+    } else {
+        (ducking.0 - step).max(target)
+    };
+    if ducking.0 != ducked {
+        ducking.0 = ducked;
+    }
+}
+
+// every sound is set to its volume each frame - so fades, ducking and the settings all
+// apply to sounds that are already playing
+fn mix(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    ducking: Res<Ducking>,
+    mut playing_query: Query<(Entity, &mut Mixed, Option<&AudioSink>)>,
+) {
+    for (entity, mut mixed, audio_sink) in playing_query.iter_mut() {
+        // the sink shows up a frame or so after the sound is spawned
+        let Some(audio_sink) = audio_sink else {
+            continue;
+        };
+
+        // paused along with the game
+        if !audio_sink.is_paused() {
+            mixed.fade_in.tick(time.delta());
+            if let Some(fade_out) = mixed.fade_out.as_mut() {
+                fade_out.tick(time.delta());
+            }
+        }
+
+        if mixed.fade_out.as_ref().is_some_and(Timer::finished) {
+            audio_sink.stop();
+            commands.entity(entity).despawn();
+        } else {
+            audio_sink.set_volume(bus_volume(mixed.bus, &settings, &ducking) * mixed.level());
+        }
     }
 }
 
-fn stop_all_sounds(mut audio_sink_query: Query<&mut AudioSink>) {
-    for audio_sink in audio_sink_query.iter_mut() {
-        audio_sink.stop();
+fn fade_out_music(mut playing_query: Query<&mut Mixed>) {
+    for mut mixed in playing_query.iter_mut() {
+        if mixed.bus == AudioBus::Music {
+            mixed.start_fade_out(MUSIC_FADE);
+        }
     }
 }
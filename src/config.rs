@@ -0,0 +1,20 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Where a file of ours lives in the user's config directory - None if there isn't one
+/// (no home directory, or running in a browser)
+pub fn config_path(file: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("snowglobe").join(file))
+}
+
+fn config_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
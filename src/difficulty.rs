@@ -0,0 +1,158 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// score lost for a wrong answer on Normal
+pub const MISTAKE_PENALTY: f32 = 1250.;
+
+// edit this to set up the custom difficulty
+pub const CUSTOM_DIFFICULTY_FILE: &str = "snowglobe/custom.difficulty.ron";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Custom,
+}
+
+impl DifficultyPreset {
+    // the order the title screen button steps through them
+    pub fn next(self) -> Self {
+        match self {
+            DifficultyPreset::Easy => DifficultyPreset::Normal,
+            DifficultyPreset::Normal => DifficultyPreset::Hard,
+            DifficultyPreset::Hard => DifficultyPreset::Custom,
+            DifficultyPreset::Custom => DifficultyPreset::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "easy",
+            DifficultyPreset::Normal => "normal",
+            DifficultyPreset::Hard => "hard",
+            DifficultyPreset::Custom => "custom",
+        }
+    }
+}
+
+/// How each round's own timer, penalty and hit areas are scaled - 1.0 plays the round as it was designed
+#[derive(Serialize, Deserialize, TypeUuid, TypePath, Debug, Clone, Copy, PartialEq)]
+#[uuid = "0c2e7f4a-5b1d-4d6e-8f3a-9e2b7c1d4a58"]
+pub struct DifficultySettings {
+    pub timer_scale: f32,
+    pub penalty_scale: f32,
+    // how forgiving the snap radius (and the periodic table and grocery click areas) are
+    pub tolerance_scale: f32,
+}
+
+impl DifficultySettings {
+    pub const EASY: DifficultySettings = DifficultySettings {
+        timer_scale: 1.5,
+        penalty_scale: 0.5,
+        tolerance_scale: 1.4,
+    };
+
+    pub const NORMAL: DifficultySettings = DifficultySettings {
+        timer_scale: 1.0,
+        penalty_scale: 1.0,
+        tolerance_scale: 1.0,
+    };
+
+    pub const HARD: DifficultySettings = DifficultySettings {
+        timer_scale: 0.7,
+        penalty_scale: 2.0,
+        tolerance_scale: 0.6,
+    };
+}
+
+/// The difficulty every puzzle reads its timer, penalty and snap tolerance from
+// saved with a replay, so it plays back with the same timers and penalties
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    preset: DifficultyPreset,
+    settings: DifficultySettings,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty {
+            preset: DifficultyPreset::Normal,
+            settings: DifficultySettings::NORMAL,
+        }
+    }
+}
+
+impl Difficulty {
+    /// Switch presets - Custom uses the settings from the custom difficulty file,
+    /// or Normal's if it hasn't loaded
+    pub fn set(&mut self, preset: DifficultyPreset, custom: Option<&DifficultySettings>) {
+        self.preset = preset;
+        self.settings = match preset {
+            DifficultyPreset::Easy => DifficultySettings::EASY,
+            DifficultyPreset::Normal => DifficultySettings::NORMAL,
+            DifficultyPreset::Hard => DifficultySettings::HARD,
+            DifficultyPreset::Custom => custom.copied().unwrap_or(DifficultySettings::NORMAL),
+        };
+    }
+
+    pub fn preset(&self) -> DifficultyPreset {
+        self.preset
+    }
+
+    pub fn timer(&self, seconds: u64) -> Duration {
+        Duration::from_secs_f32(seconds as f32 * self.settings.timer_scale)
+    }
+
+    pub fn penalty(&self, penalty: f32) -> f32 {
+        penalty * self.settings.penalty_scale
+    }
+
+    pub fn tolerance(&self, radius: f32) -> f32 {
+        radius * self.settings.tolerance_scale
+    }
+}
+
+#[derive(Resource)]
+pub struct CustomDifficulty(pub Handle<DifficultySettings>);
+
+#[derive(Default)]
+struct DifficultySettingsLoader;
+
+impl AssetLoader for DifficultySettingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let settings = ron::de::from_bytes::<DifficultySettings>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(settings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["difficulty.ron"]
+    }
+}
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<DifficultySettings>()
+            .init_asset_loader::<DifficultySettingsLoader>()
+            .init_resource::<Difficulty>()
+            .add_systems(Startup, load_custom_difficulty);
+    }
+}
+
+fn load_custom_difficulty(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CustomDifficulty(asset_server.load(CUSTOM_DIFFICULTY_FILE)));
+}
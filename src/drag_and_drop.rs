@@ -0,0 +1,883 @@
+use bevy::prelude::*;
+use bevy::text::BreakLineOn;
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::audio::{AddSound, SoundDefinition};
+use crate::difficulty::{Difficulty, MISTAKE_PENALTY};
+use crate::palette::Palette;
+use crate::pause::PauseToggle;
+use crate::picking::sprite_contains;
+use crate::playlist::Playlist;
+use crate::pointer::Pointer;
+use crate::puzzle::{AddSnowglobePuzzle, PuzzleId, PuzzleStage, SnowglobePuzzle};
+use crate::puzzle_definition::DRAG_AND_DROP_PUZZLES;
+use crate::puzzle_definition::{PuzzleDefinition, PuzzleDefinitions, TileTarget};
+use crate::score::TIME_BONUS_POSITION;
+use crate::timer_bar::TimerResource;
+use crate::tween::{Easing, Tween};
+use crate::GameState;
+use crate::PauseState;
+use crate::PuzzleState;
+use crate::Score;
+use crate::ScoreReason;
+use crate::SoundEvent;
+use crate::SoundsEnum;
+
+#[derive(Component)]
+pub struct AnswerSlot;
+
+#[derive(Component)]
+pub struct SpriteData {
+    pub sprite_placed: bool,
+    pub starting_location: Vec3,
+    pub correct_location: Vec3,
+    pub target: TileTarget,
+}
+
+impl SpriteData {
+    fn is_correct_drop(&self, drop_location: Vec3, snap_radius: f32) -> bool {
+        match self.target {
+            TileTarget::Slot(_) => {
+                let sprite_to_answer_vector = Vec2::new(
+                    drop_location.x - self.correct_location.x,
+                    drop_location.y - self.correct_location.y,
+                );
+                sprite_to_answer_vector.length() < snap_radius
+            }
+            TileTarget::Anywhere => true,
+            TileTarget::Nowhere => false,
+        }
+    }
+}
+
+// drawn behind the tile (or answer slot) that has the keyboard focus
+#[derive(Component)]
+struct FocusHighlight;
+
+const FOCUS_HIGHLIGHT_BORDER: f32 = 10.;
+// how far a picked up tile is raised when there are no answer slots to carry it to
+const KEYBOARD_LIFT: f32 = 40.;
+// a tile being dragged is drawn over every other tile, and any placed in their slots
+const DRAGGED_TILE_Z: f32 = 4.;
+
+const SNAP_BACK_TIME: Duration = Duration::from_millis(450);
+const SNAP_BACK_SHAKE: f32 = 12.;
+const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+// extra artwork that is shown with the puzzle (the staff, the family tree...)
+#[derive(Component)]
+pub struct PuzzleDecoration;
+
+/// The rules for the drag and drop puzzle currently being played
+#[derive(Resource, Deserialize, Debug, Clone)]
+pub struct DragAndDropParameters {
+    pub required_placements: usize,
+    // how close to its answer slot a tile has to be dropped to snap into place
+    #[serde(default = "default_snap_radius")]
+    pub snap_radius: f32,
+    // score lost for each wrong drop
+    #[serde(default = "default_penalty")]
+    pub penalty: f32,
+}
+
+fn default_snap_radius() -> f32 {
+    75.
+}
+
+fn default_penalty() -> f32 {
+    MISTAKE_PENALTY
+}
+
+#[derive(Resource, Default)]
+pub struct SpriteSelectionState {
+    // the tile being dragged, and where it is from the pointer
+    sprite: Option<(Entity, Vec2)>,
+}
+
+/// Playing with the keyboard - the arrow keys (or tab) move the focus between the tiles,
+/// enter picks one up, the arrows then choose an answer slot and enter drops it there
+#[derive(Resource, Default)]
+pub struct KeyboardFocus {
+    tile: Option<Entity>,
+    // the focused tile has been picked up
+    carrying: bool,
+    // where it will be dropped - None if the puzzle has no answer slots
+    slot: Option<Entity>,
+    // the highlight only shows up once a key has been pressed, so it doesn't bother mouse players
+    active: bool,
+}
+
+/// A round that is laid out by a definition file - drag the tiles onto their answer slots
+pub struct DragAndDropPuzzle {
+    pub id: PuzzleId,
+    pub definition_file: &'static str,
+    pub stage: PuzzleStage,
+    // added to the SoundRegistry, for the definition file to name as the round's song
+    pub song: (SoundsEnum, SoundDefinition),
+}
+
+impl SnowglobePuzzle for DragAndDropPuzzle {
+    fn id(&self) -> PuzzleId {
+        self.id
+    }
+
+    fn stage(&self) -> PuzzleStage {
+        self.stage.clone()
+    }
+
+    // the systems are shared by every drag and drop puzzle (see DragAndDropPlugin) - all
+    // that's needed here is the song and getting the definition file loaded
+    fn build(&self, app: &mut App) {
+        let (song, definition) = self.song.clone();
+        app.add_sound(song, definition)
+            .init_resource::<PuzzleDefinitions>();
+        app.world
+            .resource_mut::<PuzzleDefinitions>()
+            .add(self.id, self.definition_file);
+    }
+}
+
+/// Runs every puzzle that has a definition file
+pub struct DragAndDropPlugin;
+
+impl Plugin for DragAndDropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpriteSelectionState>()
+            .init_resource::<KeyboardFocus>()
+            .add_systems(
+                OnEnter(GameState::Puzzle),
+                setup.run_if(drag_and_drop_puzzle_is_current),
+            )
+            .add_systems(OnEnter(PuzzleState::InProgress), begin_drag_and_drop_puzzle)
+            .add_systems(
+                Update,
+                play_drag_and_drop_puzzle
+                    .run_if(in_state(GameState::Puzzle))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(resource_exists::<DragAndDropParameters>()),
+            )
+            .add_systems(
+                Update,
+                (play_drag_and_drop_with_keyboard, update_focus_highlight)
+                    .chain()
+                    // esc puts a picked up tile back rather than pausing
+                    .before(PauseToggle)
+                    .run_if(in_state(PuzzleState::InProgress))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(resource_exists::<DragAndDropParameters>()),
+            )
+            .add_systems(
+                Update,
+                check_for_puzzle_completion
+                    .run_if(in_state(PuzzleState::InProgress))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(resource_exists::<DragAndDropParameters>()),
+            )
+            .add_systems(OnExit(GameState::Puzzle), cleanup);
+
+        for puzzle in DRAG_AND_DROP_PUZZLES {
+            app.add_snowglobe_puzzle(puzzle);
+        }
+    }
+}
+
+fn drag_and_drop_puzzle_is_current(
+    playlist: Res<Playlist>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+) -> bool {
+    playlist
+        .current()
+        .is_some_and(|id| puzzle_definitions.contains(id))
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    playlist: Res<Playlist>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+    definition_assets: Res<Assets<PuzzleDefinition>>,
+    difficulty: Res<Difficulty>,
+    palette: Res<Palette>,
+) {
+    let Some(definition) = puzzle_definitions.get(playlist.current(), &definition_assets) else {
+        error!(
+            "the puzzle definition for {:?} is not loaded",
+            playlist.current()
+        );
+        return;
+    };
+
+    // the definition file has the values for Normal
+    let parameters = &definition.parameters;
+    commands.insert_resource(DragAndDropParameters {
+        required_placements: parameters.required_placements,
+        snap_radius: difficulty.tolerance(parameters.snap_radius),
+        penalty: difficulty.penalty(parameters.penalty),
+    });
+    commands.insert_resource(SpriteSelectionState::default());
+    commands.insert_resource(KeyboardFocus::default());
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::GOLD,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        FocusHighlight,
+    ));
+
+    // generate the answer slots
+    for answer_slot in definition.slots.iter() {
+        let mut answer_slot_entity = commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(definition.slot_style.filename.clone()),
+                sprite: Sprite {
+                    custom_size: Some(definition.icon_size),
+                    color: palette.puzzle_color(
+                        definition.slot_style.color,
+                        definition.slot_style.colorblind_color,
+                    ),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+
+                transform: Transform {
+                    translation: answer_slot.position,
+                    ..default()
+                },
+
+                ..default()
+            },
+            AnswerSlot,
+        ));
+
+        if let Some(text) = &answer_slot.text {
+            answer_slot_entity.with_children(|parent| {
+                parent.spawn(Text2dBundle {
+                    text: Text {
+                        sections: vec![TextSection::new(
+                            text.clone(),
+                            TextStyle {
+                                font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                                font_size: definition.slot_style.font_size,
+                                color: Color::BLACK,
+                            },
+                        )],
+                        alignment: TextAlignment::Center,
+                        linebreak_behavior: BreakLineOn::NoWrap,
+                    },
+                    // ensure the text is drawn on top of the box
+                    transform: Transform::from_translation(Vec3::Z),
+                    ..default()
+                });
+            });
+        }
+    }
+
+    // place the tiles
+    for tile in definition.tiles.iter() {
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(tile.filename.clone()),
+                sprite: Sprite {
+                    custom_size: Some(definition.icon_size),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+
+                transform: Transform {
+                    translation: tile.starting_location,
+                    ..default()
+                },
+
+                ..default()
+            },
+            SpriteData {
+                sprite_placed: false,
+                starting_location: tile.starting_location,
+                correct_location: definition.correct_location(tile),
+                target: tile.target,
+            },
+        ));
+    }
+
+    // generate any extra artwork
+    for decoration in definition.decorations.iter() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: decoration.color,
+                    custom_size: Some(decoration.size),
+                    ..default()
+                },
+                texture: asset_server.load(decoration.filename.clone()),
+                visibility: Visibility::Hidden,
+                transform: Transform {
+                    translation: decoration.position,
+                    ..default()
+                },
+
+                ..default()
+            },
+            PuzzleDecoration,
+        ));
+    }
+}
+
+fn begin_drag_and_drop_puzzle(
+    mut visibility_query: Query<
+        &mut Visibility,
+        Or<(With<AnswerSlot>, With<SpriteData>, With<PuzzleDecoration>)>,
+    >,
+) {
+    for mut visibility in visibility_query.iter_mut() {
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn play_drag_and_drop_puzzle(
+    mut sprite_selection_state: ResMut<SpriteSelectionState>,
+    mut sound_event_writer: EventWriter<SoundEvent>,
+    pointer: Res<Pointer>,
+    mut commands: Commands,
+    mut sprites: Query<(Entity, &mut SpriteData), Without<Tween>>,
+    pictures: Query<&Sprite, With<SpriteData>>,
+    mut transforms: Query<&mut Transform>,
+    mut score: ResMut<Score>,
+    parameters: Res<DragAndDropParameters>,
+    playlist: Res<Playlist>,
+    timer_resource: Res<TimerResource>,
+) {
+    let Some(puzzle) = playlist.current() else {
+        return;
+    };
+
+    if pointer.just_released() {
+        if let Some((released_entity, _)) = sprite_selection_state.sprite {
+            if let Ok((entity, mut sprite)) = sprites.get_mut(released_entity) {
+                drop_tile(
+                    &mut commands,
+                    entity,
+                    &mut sprite,
+                    transforms.get(entity).unwrap(),
+                    &parameters,
+                    puzzle,
+                    &timer_resource,
+                    &mut score,
+                    &mut sound_event_writer,
+                );
+            }
+        }
+
+        sprite_selection_state.sprite = None;
+
+        return;
+    }
+
+    // off the edge of the layout
+    let Some(cursor) = pointer.world_position() else {
+        return;
+    };
+
+    if pointer.pressed() {
+        if let Some((entity, offset)) = sprite_selection_state.sprite {
+            let mut sprite_pos = transforms.get_mut(entity).unwrap();
+
+            sprite_pos.translation.x = cursor.x + offset.x;
+            sprite_pos.translation.y = cursor.y + offset.y;
+        }
+    }
+
+    if pointer.just_pressed() {
+        // where tiles overlap, the one on top is picked up
+        let picked = sprites
+            .iter()
+            // placed tiles are locked in, and tiles on the move can't be caught
+            .filter(|(_, sprite)| !sprite.sprite_placed)
+            .filter_map(|(entity, _)| {
+                let transform = transforms.get(entity).ok()?;
+                sprite_contains(pictures.get(entity).ok()?, transform, cursor)
+                    .then_some((entity, transform.translation))
+            })
+            .max_by(|(_, a), (_, b)| a.z.total_cmp(&b.z));
+
+        if let Some((entity, translation)) = picked {
+            sprite_selection_state.sprite = Some((entity, translation.truncate() - cursor));
+            transforms.get_mut(entity).unwrap().translation.z = DRAGGED_TILE_Z;
+        }
+    }
+}
+
+// a tile has been let go of where it is - it either stays put (settling into its answer slot)
+// or flies back to where it started and costs a penalty
+fn drop_tile(
+    commands: &mut Commands,
+    entity: Entity,
+    sprite: &mut SpriteData,
+    transform: &Transform,
+    parameters: &DragAndDropParameters,
+    puzzle: PuzzleId,
+    timer_resource: &TimerResource,
+    score: &mut Score,
+    sound_event_writer: &mut EventWriter<SoundEvent>,
+) {
+    if sprite.is_correct_drop(transform.translation, parameters.snap_radius) {
+        sound_event_writer.send(SoundEvent::new(SoundsEnum::Success));
+        sprite.sprite_placed = true;
+
+        // tiles with an answer slot settle into it - the rest stay where they were dropped,
+        // back down among the other tiles
+        let settled = match sprite.target {
+            TileTarget::Slot(_) => Transform::from_xyz(
+                sprite.correct_location.x,
+                sprite.correct_location.y,
+                sprite.correct_location.z + 2.0, // to put it on top of the answer slot
+            ),
+            _ => transform.with_translation(
+                transform
+                    .translation
+                    .truncate()
+                    .extend(sprite.starting_location.z),
+            ),
+        };
+        commands.entity(entity).insert(Tween::new(
+            *transform,
+            settled,
+            SETTLE_TIME,
+            Easing::OutBack,
+        ));
+    } else {
+        sound_event_writer.send(SoundEvent::new(SoundsEnum::Failure));
+
+        score.change(
+            -parameters.penalty,
+            puzzle,
+            ScoreReason::WrongTile,
+            timer_resource.time_remaining.elapsed(),
+            transform.translation.truncate(),
+        );
+
+        commands.entity(entity).insert(
+            Tween::new(
+                *transform,
+                Transform::from_translation(sprite.starting_location),
+                SNAP_BACK_TIME,
+                Easing::InOutCubic,
+            )
+            .with_shake(SNAP_BACK_SHAKE),
+        );
+    }
+}
+
+// tiles (or slots) in reading order - top row first, left to right
+fn reading_order(mut entities: Vec<(Entity, Vec3)>) -> Vec<Entity> {
+    entities.sort_by(|(_, a), (_, b)| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+    entities.into_iter().map(|(entity, _)| entity).collect()
+}
+
+// step forwards or backwards through a list, wrapping around - starts at the top if nothing is focused yet
+fn cycle(entities: &[Entity], current: Option<Entity>, forwards: bool) -> Option<Entity> {
+    let count = entities.len();
+    let index = match current.and_then(|current| entities.iter().position(|e| *e == current)) {
+        Some(index) if forwards => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        None => 0,
+    };
+    entities.get(index).copied()
+}
+
+fn play_drag_and_drop_with_keyboard(
+    mut keyboard_focus: ResMut<KeyboardFocus>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut commands: Commands,
+    mut sound_event_writer: EventWriter<SoundEvent>,
+    // tiles still on their way somewhere can't be picked up
+    mut sprites: Query<(Entity, &mut SpriteData), Without<Tween>>,
+    slots: Query<Entity, With<AnswerSlot>>,
+    mut transforms: Query<&mut Transform>,
+    mut score: ResMut<Score>,
+    parameters: Res<DragAndDropParameters>,
+    playlist: Res<Playlist>,
+    timer_resource: Res<TimerResource>,
+) {
+    let Some(puzzle) = playlist.current() else {
+        return;
+    };
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let tab = keys.just_pressed(KeyCode::Tab);
+    let next = keys.any_just_pressed([KeyCode::Right, KeyCode::Down]) || (tab && !shift);
+    let previous = keys.any_just_pressed([KeyCode::Left, KeyCode::Up]) || (tab && shift);
+    let confirm = keys.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]);
+    let cancel = keys.just_pressed(KeyCode::Escape) && keyboard_focus.carrying;
+
+    if !(next || previous || confirm || cancel) {
+        return;
+    }
+    keyboard_focus.active = true;
+
+    let slot_order = reading_order(
+        slots
+            .iter()
+            .map(|entity| (entity, transforms.get(entity).unwrap().translation))
+            .collect(),
+    );
+
+    if !keyboard_focus.carrying {
+        let tile_order = reading_order(
+            sprites
+                .iter()
+                .filter(|(_, sprite)| !sprite.sprite_placed)
+                .map(|(entity, _)| (entity, transforms.get(entity).unwrap().translation))
+                .collect(),
+        );
+        // the focused tile may have been placed (or dragged in) with the mouse
+        if keyboard_focus
+            .tile
+            .is_some_and(|tile| !tile_order.contains(&tile))
+        {
+            keyboard_focus.tile = None;
+        }
+
+        if next || previous {
+            keyboard_focus.tile = cycle(&tile_order, keyboard_focus.tile, next);
+        } else if confirm {
+            // enter with nothing focused just focuses the first tile
+            if keyboard_focus.tile.is_none() {
+                keyboard_focus.tile = tile_order.first().copied();
+                return;
+            }
+            // pick it up - it goes straight over the first answer slot
+            let Some((_, sprite)) = keyboard_focus.tile.and_then(|tile| sprites.get(tile).ok())
+            else {
+                return;
+            };
+            let starting_location = sprite.starting_location;
+            keyboard_focus.carrying = true;
+            keyboard_focus.slot = slot_order.first().copied();
+            carry_tile(&keyboard_focus, starting_location, &mut transforms);
+        }
+        return;
+    }
+
+    let Some(tile) = keyboard_focus.tile else {
+        keyboard_focus.carrying = false;
+        return;
+    };
+    let Ok((_, mut sprite)) = sprites.get_mut(tile) else {
+        keyboard_focus.carrying = false;
+        return;
+    };
+
+    if cancel {
+        commands.entity(tile).insert(Tween::new(
+            *transforms.get(tile).unwrap(),
+            Transform::from_translation(sprite.starting_location),
+            SNAP_BACK_TIME,
+            Easing::InOutCubic,
+        ));
+        keyboard_focus.carrying = false;
+        keyboard_focus.slot = None;
+        // it was only putting the tile back - don't pause the game as well
+        keys.clear_just_pressed(KeyCode::Escape);
+    } else if confirm {
+        drop_tile(
+            &mut commands,
+            tile,
+            &mut sprite,
+            transforms.get(tile).unwrap(),
+            &parameters,
+            puzzle,
+            &timer_resource,
+            &mut score,
+            &mut sound_event_writer,
+        );
+        keyboard_focus.carrying = false;
+        keyboard_focus.slot = None;
+    } else {
+        keyboard_focus.slot = cycle(&slot_order, keyboard_focus.slot, next);
+        carry_tile(&keyboard_focus, sprite.starting_location, &mut transforms);
+    }
+}
+
+// hold the picked up tile over the chosen slot - or just lift it if the puzzle has no slots
+fn carry_tile(
+    keyboard_focus: &KeyboardFocus,
+    starting_location: Vec3,
+    transforms: &mut Query<&mut Transform>,
+) {
+    let Some(tile) = keyboard_focus.tile else {
+        return;
+    };
+
+    // with no slots to go over it is just lifted out of the line - from where it started, so
+    // it doesn't go up again with every key press
+    let destination = match keyboard_focus.slot {
+        Some(slot) => transforms.get(slot).unwrap().translation,
+        None => starting_location + Vec3::new(0.0, KEYBOARD_LIFT, 0.0),
+    };
+
+    // above the slot (and any other tile)
+    transforms.get_mut(tile).unwrap().translation = destination + Vec3::new(0.0, 0.0, 3.0);
+}
+
+// the focus highlight sits just behind whatever has the keyboard focus
+fn update_focus_highlight(
+    keyboard_focus: Res<KeyboardFocus>,
+    sprites: Query<&SpriteData>,
+    targets: Query<(&Transform, &Sprite), Without<FocusHighlight>>,
+    mut highlight_query: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        With<FocusHighlight>,
+    >,
+) {
+    let target = match (keyboard_focus.carrying, keyboard_focus.slot) {
+        (true, Some(slot)) => Some(slot),
+        _ => keyboard_focus.tile,
+    };
+    // a tile that has gone into its slot no longer needs it
+    let target = target.filter(|target| {
+        keyboard_focus.active
+            && !sprites
+                .get(*target)
+                .is_ok_and(|sprite| sprite.sprite_placed)
+    });
+
+    for (mut transform, mut sprite, mut visibility) in highlight_query.iter_mut() {
+        match target.and_then(|target| targets.get(target).ok()) {
+            Some((target_transform, target_sprite)) => {
+                transform.translation = target_transform.translation - Vec3::new(0.0, 0.0, 0.5);
+                sprite.custom_size = target_sprite
+                    .custom_size
+                    .map(|size| size + Vec2::splat(FOCUS_HIGHLIGHT_BORDER * 2.0));
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn check_for_puzzle_completion(
+    mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
+    sprites: Query<&SpriteData>,
+    mut score: ResMut<Score>,
+    timer_resource: Res<TimerResource>,
+    mut sound_event_writer: EventWriter<SoundEvent>,
+    parameters: Res<DragAndDropParameters>,
+    playlist: Res<Playlist>,
+) {
+    let successful_sprite_count = sprites.iter().filter(|sprite| sprite.sprite_placed).count();
+
+    if successful_sprite_count >= parameters.required_placements {
+        let time_remaining = (timer_resource.time_remaining.duration()
+            - timer_resource.time_remaining.elapsed())
+        .as_millis() as f32;
+
+        if let Some(puzzle) = playlist.current() {
+            score.change(
+                time_remaining,
+                puzzle,
+                ScoreReason::TimeBonus,
+                timer_resource.time_remaining.elapsed(),
+                TIME_BONUS_POSITION,
+            );
+        }
+
+        sound_event_writer.send(SoundEvent::new(SoundsEnum::HoHoHo));
+
+        next_puzzle_state.set(PuzzleState::Complete);
+    }
+}
+
+fn cleanup(
+    mut commands: Commands,
+    puzzle_entity_query: Query<
+        Entity,
+        Or<(
+            With<AnswerSlot>,
+            With<SpriteData>,
+            With<PuzzleDecoration>,
+            With<FocusHighlight>,
+        )>,
+    >,
+) {
+    for puzzle_entity in puzzle_entity_query.iter() {
+        commands.entity(puzzle_entity).despawn_recursive();
+    }
+
+    commands.remove_resource::<DragAndDropParameters>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_definition::TURKEY_MISTLETOE;
+    use crate::testing::TestGame;
+
+    // a tile dropped somewhere that doesn't count - on someone else's slot if there are any
+    fn wrong_drop(game: &mut TestGame) -> (Vec2, Vec2) {
+        let snap_radius = game.world().resource::<DragAndDropParameters>().snap_radius;
+        let mut slots = game
+            .world_mut()
+            .query_filtered::<&Transform, With<AnswerSlot>>();
+        let slots: Vec<Vec3> = slots
+            .iter(game.world())
+            .map(|transform| transform.translation)
+            .collect();
+        let mut tiles = game.world_mut().query::<&SpriteData>();
+
+        tiles
+            .iter(game.world())
+            .filter(|tile| tile.target != TileTarget::Anywhere)
+            .find_map(|tile| {
+                let below = tile.starting_location - Vec3::new(0.0, 150.0, 0.0);
+                slots
+                    .iter()
+                    .copied()
+                    .chain([below])
+                    .find(|drop| !tile.is_correct_drop(*drop, snap_radius))
+                    .map(|drop| (tile.starting_location.truncate(), drop.truncate()))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn every_puzzle_can_be_solved() {
+        for puzzle in DRAG_AND_DROP_PUZZLES {
+            let mut game = TestGame::new();
+            game.play_round(puzzle.id);
+
+            for (from, to) in game.solution() {
+                game.drag(from, to);
+            }
+            game.run_for(SETTLE_TIME);
+
+            assert_eq!(
+                game.puzzle_state(),
+                PuzzleState::Complete,
+                "{:?}",
+                puzzle.id
+            );
+            assert!(
+                matches!(
+                    game.score_changes()[..],
+                    [(ScoreReason::TimeBonus, bonus)] if bonus > 0.0
+                ),
+                "{:?}",
+                puzzle.id
+            );
+        }
+    }
+
+    #[test]
+    fn every_puzzle_can_be_solved_by_touch() {
+        for puzzle in DRAG_AND_DROP_PUZZLES {
+            let mut game = TestGame::new();
+            game.play_round(puzzle.id);
+
+            for (from, to) in game.solution() {
+                game.touch_drag(from, to);
+            }
+            game.run_for(SETTLE_TIME);
+
+            assert_eq!(
+                game.puzzle_state(),
+                PuzzleState::Complete,
+                "{:?}",
+                puzzle.id
+            );
+        }
+    }
+
+    #[test]
+    fn a_canceled_touch_drops_the_tile_where_it_is() {
+        let mut game = TestGame::new();
+        game.play_round(DRAG_AND_DROP_PUZZLES[0].id);
+        let penalty = game.world().resource::<DragAndDropParameters>().penalty;
+
+        let (from, to) = wrong_drop(&mut game);
+        game.touch_down(from);
+        game.touch_move(to);
+        game.touch_cancel(to);
+        game.run_for(SNAP_BACK_TIME);
+
+        assert_eq!(
+            game.score_changes(),
+            vec![(ScoreReason::WrongTile, -penalty)]
+        );
+    }
+
+    #[test]
+    fn a_carried_tile_is_lifted_once_however_many_arrows_are_pressed() {
+        // no answer slots to carry the tile over
+        let mut game = TestGame::new();
+        game.play_round(TURKEY_MISTLETOE);
+
+        // the first enter focuses a tile, the second picks it up
+        game.press_key(KeyCode::Return);
+        game.press_key(KeyCode::Return);
+        for _ in 0..5 {
+            game.press_key(KeyCode::Right);
+        }
+
+        let tile = game.world().resource::<KeyboardFocus>().tile.unwrap();
+        let starting_location = game
+            .world()
+            .get::<SpriteData>(tile)
+            .unwrap()
+            .starting_location;
+        let translation = game.world().get::<Transform>(tile).unwrap().translation;
+        assert_eq!(
+            translation.truncate(),
+            (starting_location + Vec3::new(0.0, KEYBOARD_LIFT, 0.0)).truncate()
+        );
+    }
+
+    #[test]
+    fn wrong_drops_cost_a_penalty_and_go_back() {
+        for puzzle in DRAG_AND_DROP_PUZZLES {
+            let mut game = TestGame::new();
+            game.play_round(puzzle.id);
+            let penalty = game.world().resource::<DragAndDropParameters>().penalty;
+
+            let (from, to) = wrong_drop(&mut game);
+            game.drag(from, to);
+            game.run_for(SNAP_BACK_TIME);
+
+            assert_eq!(
+                game.puzzle_state(),
+                PuzzleState::InProgress,
+                "{:?}",
+                puzzle.id
+            );
+            assert_eq!(
+                game.score_changes(),
+                vec![(ScoreReason::WrongTile, -penalty)],
+                "{:?}",
+                puzzle.id
+            );
+            let mut tiles = game.world_mut().query::<(&SpriteData, &Transform)>();
+            assert!(
+                tiles
+                    .iter(game.world())
+                    .all(|(tile, transform)| !tile.sprite_placed
+                        && transform.translation == tile.starting_location),
+                "{:?}",
+                puzzle.id
+            );
+
+            game.run_out_the_clock();
+            assert_eq!(
+                game.puzzle_state(),
+                PuzzleState::Complete,
+                "{:?}",
+                puzzle.id
+            );
+            assert_eq!(game.score_changes().len(), 1, "{:?}", puzzle.id);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+use bevy::window::PrimaryWindow;
+
+use crate::picking::Letterbox;
+use crate::pointer::PointerSet;
+use crate::replay::playing_replay;
+
+// how fast the cursor moves with the left stick pushed all the way, in pixels a second
+const CURSOR_SPEED: f32 = 900.;
+
+pub struct GamepadPlugin;
+
+/// Controllers drive the mouse - the left stick moves the cursor, A clicks and the d-pad
+/// jumps the cursor between the buttons on screen. Everything that already listens for
+/// `CursorMoved` and the left mouse button (the puzzles and the menus) works unchanged
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                move_cursor_with_stick,
+                move_cursor_between_buttons,
+                click_with_a,
+            )
+                .chain()
+                // after the controllers are read, and before the ui works out what is hovered
+                .after(InputSystem)
+                .before(PointerSet)
+                .before(UiSystem::Focus)
+                // a replay has the pointer to itself
+                .run_if(not(playing_replay)),
+        );
+    }
+}
+
+fn move_cursor_with_stick(
+    time: Res<Time>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut cursor_moved_events: EventWriter<CursorMoved>,
+) {
+    let Ok((window_entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+
+    // everyone's stick counts - whoever has the controller in their hands
+    let stick = gamepads.iter().fold(Vec2::ZERO, |stick, gamepad| {
+        stick
+            + Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                    .unwrap_or(0.0),
+                axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                    .unwrap_or(0.0),
+            )
+    });
+    if stick == Vec2::ZERO {
+        return;
+    }
+
+    let cursor = window
+        .cursor_position()
+        .unwrap_or(Vec2::new(window.width() / 2.0, window.height() / 2.0));
+    // the stick points up, the cursor is measured down from the top of the window
+    let step = CURSOR_SPEED * time.delta_seconds();
+    let moved = cursor + Vec2::new(stick.x, -stick.y).clamp_length_max(1.0) * step;
+    let position = moved.clamp(Vec2::ZERO, Vec2::new(window.width(), window.height()));
+
+    set_cursor(
+        window_entity,
+        &mut window,
+        position,
+        &mut cursor_moved_events,
+    );
+}
+
+fn move_cursor_between_buttons(
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    buttons: Query<(&Node, &GlobalTransform, &ComputedVisibility), With<Button>>,
+    letterbox: Res<Letterbox>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut cursor_moved_events: EventWriter<CursorMoved>,
+) {
+    let pressed = |button_type| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let next = pressed(GamepadButtonType::DPadDown) || pressed(GamepadButtonType::DPadRight);
+    let previous = pressed(GamepadButtonType::DPadUp) || pressed(GamepadButtonType::DPadLeft);
+    if !(next || previous) {
+        return;
+    }
+
+    let Ok((window_entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+
+    // the middle of every button that can be clicked right now, in reading order
+    let mut centers: Vec<Vec2> = buttons
+        .iter()
+        .filter(|(node, _, visibility)| {
+            visibility.is_visible_in_hierarchy() && node.size() != Vec2::ZERO
+        })
+        // the ui is laid out over the whole window, in layout pixels
+        .map(|(_, transform, _)| transform.translation().truncate() * letterbox.scale)
+        .collect();
+    if centers.is_empty() {
+        return;
+    }
+    centers.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    // step on from whichever button the cursor is closest to
+    let current = window.cursor_position().map(|cursor| {
+        centers
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.distance(cursor).total_cmp(&b.distance(cursor)))
+            .map(|(index, center)| (index, center.distance(cursor) < 1.0))
+            .unwrap()
+    });
+    let count = centers.len();
+    let index = match current {
+        // the cursor isn't on a button yet - go to the nearest one
+        Some((index, false)) => index,
+        Some((index, true)) if next => (index + 1) % count,
+        Some((index, true)) => (index + count - 1) % count,
+        None => 0,
+    };
+
+    set_cursor(
+        window_entity,
+        &mut window,
+        centers[index],
+        &mut cursor_moved_events,
+    );
+}
+
+// A is the left mouse button
+fn click_with_a(
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut mouse_button_input: ResMut<Input<MouseButton>>,
+) {
+    let a = |gamepad| GamepadButton::new(gamepad, GamepadButtonType::South);
+
+    if gamepads
+        .iter()
+        .any(|gamepad| gamepad_buttons.just_pressed(a(gamepad)))
+    {
+        mouse_button_input.press(MouseButton::Left);
+    }
+    if gamepads
+        .iter()
+        .any(|gamepad| gamepad_buttons.just_released(a(gamepad)))
+    {
+        mouse_button_input.release(MouseButton::Left);
+    }
+}
+
+// moves the real cursor too, so there is something to see where the stick is pointing
+fn set_cursor(
+    window_entity: Entity,
+    window: &mut Window,
+    position: Vec2,
+    cursor_moved_events: &mut EventWriter<CursorMoved>,
+) {
+    window.set_cursor_position(Some(position));
+    cursor_moved_events.send(CursorMoved {
+        window: window_entity,
+        position,
+    });
+}
@@ -1,39 +1,64 @@
 use bevy::prelude::*;
 
+use crate::audio::{AddSound, SoundDefinition};
+use crate::difficulty::{Difficulty, MISTAKE_PENALTY};
+use crate::pointer::Pointer;
+use crate::puzzle::{puzzle_is, PuzzleId, PuzzleStage, SnowglobePuzzle};
 use crate::timer_bar::TimerResource;
 use crate::GameState;
+use crate::PauseState;
 use crate::PuzzleState;
 use crate::Score;
-use crate::ScoreChange;
+use crate::ScoreReason;
 use crate::SoundEvent;
 use crate::SoundsEnum;
 
 #[derive(Component)]
 struct StoreLayout;
 
-#[derive(Resource)]
-struct CursorPositionState {
-    cursor_pos: Vec2,
-}
-impl Default for CursorPositionState {
-    fn default() -> Self {
-        Self {
-            cursor_pos: Vec2::MAX,
+pub const GROCERY: PuzzleId = PuzzleId("grocery");
+
+// where the missing item is on the store layout
+const ANSWER_CENTER: Vec2 = Vec2::new(107.5, -155.);
+const ANSWER_HALF_SIZE: Vec2 = Vec2::new(82.5, 65.);
+
+pub struct GroceryPuzzle;
+
+impl SnowglobePuzzle for GroceryPuzzle {
+    fn id(&self) -> PuzzleId {
+        GROCERY
+    }
+
+    fn stage(&self) -> PuzzleStage {
+        PuzzleStage {
+            name: "grocery",
+            background: "snowglobe/backgrounds/grocery.png",
+            instructions: "snowglobe/text/instructions - stage 6.png",
+            answers: "snowglobe/text/answers - stage 6.png",
+            timer_seconds: Some(20),
+            answer_reveal_seconds: 3,
+            song: Some(SoundsEnum::GrocerySong),
         }
     }
-}
-pub struct GroceryPlugin;
 
-impl Plugin for GroceryPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CursorPositionState>()
-            .add_systems(OnEnter(GameState::PuzzleGrocery), setup)
-            .add_systems(OnEnter(PuzzleState::InProgress), begin_grocery_puzzle)
-            .add_systems(
-                Update,
-                play_grocery_puzzle.run_if(in_state(GameState::PuzzleGrocery)),
-            )
-            .add_systems(OnExit(GameState::PuzzleGrocery), cleanup);
+        app.add_sound(
+            SoundsEnum::GrocerySong,
+            SoundDefinition::song("snowglobe/sounds/grocery.ogg"),
+        )
+        .add_systems(OnEnter(GameState::Puzzle), setup.run_if(puzzle_is(GROCERY)))
+        .add_systems(OnEnter(PuzzleState::InProgress), begin_grocery_puzzle)
+        .add_systems(
+            Update,
+            play_grocery_puzzle
+                .run_if(in_state(GameState::Puzzle))
+                .run_if(in_state(PauseState::Running))
+                .run_if(puzzle_is(GROCERY)),
+        )
+        .add_systems(
+            OnExit(GameState::Puzzle),
+            cleanup.run_if(puzzle_is(GROCERY)),
+        );
     }
 }
 
@@ -70,56 +95,59 @@ fn begin_grocery_puzzle(
 
 fn play_grocery_puzzle(
     mut sound_event_writer: EventWriter<SoundEvent>,
-    windows: Query<&Window>,
-    mouse_button_input: Res<Input<MouseButton>>,
-    mut cursor_moved_events: EventReader<CursorMoved>,
-    mut cursor_position_resource: ResMut<CursorPositionState>,
+    pointer: Res<Pointer>,
     mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
     current_puzzle_state: Res<State<PuzzleState>>,
     timer_resource: Res<TimerResource>,
     mut score: ResMut<Score>,
+    difficulty: Res<Difficulty>,
+    pause_state: Res<State<PauseState>>,
+    // the press was made in the round - not on the button that began it, or on resume
+    mut pressed_in_round: Local<bool>,
 ) {
+    if *current_puzzle_state.get() != PuzzleState::InProgress || pause_state.is_changed() {
+        *pressed_in_round = false;
+    }
     if *current_puzzle_state.get() == PuzzleState::InProgress {
-        let window = windows.single();
-        let half_window = Vec2::new(window.resolution.width() / 2.0, window.height() / 2.0);
-
-        for ev in cursor_moved_events.iter() {
-            cursor_position_resource.cursor_pos = ev.position - half_window;
+        if pointer.just_pressed() {
+            *pressed_in_round = true;
         }
+        let clicked = pointer.just_released() && std::mem::take(&mut *pressed_in_round);
 
-        if mouse_button_input.just_released(MouseButton::Left) {
-            //println!("cursor position is: ({}, {})", cursor_position_resource.cursor_pos.x, cursor_position_resource.cursor_pos.y);
+        // a click, or a tap on the screen - anywhere but the black bars around the layout
+        if let (true, Some(click_position)) = (clicked, pointer.world_position()) {
+            let answer_half_size = Vec2::new(
+                difficulty.tolerance(ANSWER_HALF_SIZE.x),
+                difficulty.tolerance(ANSWER_HALF_SIZE.y),
+            );
+            let offset = (click_position - ANSWER_CENTER).abs();
 
-            if cursor_position_resource.cursor_pos.x > 25.
-                && cursor_position_resource.cursor_pos.x < 190.
-                && cursor_position_resource.cursor_pos.y > 90.
-                && cursor_position_resource.cursor_pos.y < 220.
-            {
+            if offset.x < answer_half_size.x && offset.y < answer_half_size.y {
                 let time_remaining = (timer_resource.time_remaining.duration()
                     - timer_resource.time_remaining.elapsed())
                 .as_millis() as f32;
 
-                let current_score = score.current_score;
-                score.score_change_vector.push(ScoreChange {
-                    initial_score: current_score,
-                    delta_score: time_remaining,
-                });
+                score.change(
+                    time_remaining,
+                    GROCERY,
+                    ScoreReason::TimeBonus,
+                    timer_resource.time_remaining.elapsed(),
+                    click_position,
+                );
 
-                sound_event_writer.send(SoundEvent(SoundsEnum::HoHoHo));
+                sound_event_writer.send(SoundEvent::new(SoundsEnum::HoHoHo));
 
                 next_puzzle_state.set(PuzzleState::Complete);
             } else {
-                if cursor_position_resource.cursor_pos.x.abs() < 5000.
-                    && cursor_position_resource.cursor_pos.y.abs() < 5000.
-                {
-                    sound_event_writer.send(SoundEvent(SoundsEnum::Failure));
-
-                    let current_score = score.current_score;
-                    score.score_change_vector.push(ScoreChange {
-                        initial_score: current_score,
-                        delta_score: -1250.0,
-                    });
-                }
+                sound_event_writer.send(SoundEvent::new(SoundsEnum::Failure));
+
+                score.change(
+                    -difficulty.penalty(MISTAKE_PENALTY),
+                    GROCERY,
+                    ScoreReason::WrongClick,
+                    timer_resource.time_remaining.elapsed(),
+                    click_position,
+                );
             }
         }
     }
@@ -129,6 +157,70 @@ fn cleanup(mut commands: Commands, store_layout_query: Query<Entity, With<StoreL
     for store_layout in store_layout_query.iter() {
         commands.entity(store_layout).despawn_recursive();
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestGame;
 
-    commands.remove_resource::<CursorPositionState>();
+    #[test]
+    fn clicking_the_answer_solves_the_puzzle() {
+        let mut game = TestGame::new();
+        game.play_round(GROCERY);
+
+        game.click(ANSWER_CENTER);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        assert!(matches!(
+            game.score_changes()[..],
+            [(ScoreReason::TimeBonus, bonus)] if bonus > 0.0
+        ));
+    }
+
+    #[test]
+    fn clicking_anywhere_else_costs_a_penalty() {
+        let mut game = TestGame::new();
+        game.play_round(GROCERY);
+
+        game.click(ANSWER_CENTER + ANSWER_HALF_SIZE * 2.0);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::InProgress);
+        assert_eq!(
+            game.score_changes(),
+            vec![(ScoreReason::WrongClick, -MISTAKE_PENALTY)]
+        );
+
+        game.run_out_the_clock();
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        assert_eq!(game.score_changes().len(), 1);
+    }
+
+    #[test]
+    fn tapping_the_answer_solves_the_puzzle() {
+        let mut game = TestGame::new();
+        game.play_round(GROCERY);
+
+        game.tap(ANSWER_CENTER);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+    }
+
+    #[test]
+    fn letting_go_of_the_click_that_began_the_round_is_free() {
+        let mut game = TestGame::new();
+        game.click_button("stage select");
+        game.click_button("grocery");
+        // nowhere near the answer
+        game.move_mouse(ANSWER_CENTER + ANSWER_HALF_SIZE * 2.0);
+
+        game.click_button_with_mouse("click to begin puzzle");
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::InProgress);
+        assert_eq!(game.score_changes(), vec![]);
+    }
 }
@@ -0,0 +1,301 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_path;
+use crate::picking::{LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::BackgroundImage;
+use crate::ButtonColors;
+use crate::GameState;
+
+// how many runs the table keeps
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const MAX_NAME_LENGTH: usize = 16;
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HighScore {
+    pub name: String,
+    pub score: i32,
+    pub mistakes: u32,
+    // yyyy-mm-dd
+    pub date: String,
+    // which rounds were played and on what difficulty, e.g. "quick game (hard)"
+    pub mode: String,
+}
+
+impl HighScore {
+    /// A run finished today
+    pub fn new(name: String, score: i32, mistakes: u32, mode: String) -> Self {
+        HighScore {
+            name,
+            score,
+            mistakes,
+            date: today(),
+            mode,
+        }
+    }
+}
+
+/// The best runs on this machine, best first - kept in the user's config directory once
+/// loaded from there, or only in memory otherwise (as in the tests)
+#[derive(Resource, Serialize, Deserialize, Debug, Default)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let Some(path) = config_path(HIGH_SCORES_FILE) else {
+            warn!("no config directory - the high scores can't be saved");
+            return HighScores::default();
+        };
+
+        let high_scores = match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("ignoring unreadable high scores in {:?}: {}", path, error);
+                HighScores::default()
+            }),
+            // nothing saved yet
+            Err(_) => HighScores::default(),
+        };
+
+        HighScores {
+            file: Some(path),
+            ..high_scores
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = &self.file else {
+            return;
+        };
+
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(path, contents).map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            error!("couldn't save the high scores to {:?}: {}", path, error);
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// Where a run would go in the table (0 is the top) - None if it wouldn't make it
+    pub fn place(&self, score: i32, mistakes: u32) -> Option<usize> {
+        // fewer mistakes breaks a tie, otherwise it goes behind - they got there first
+        let place = self
+            .entries
+            .iter()
+            .position(|entry| {
+                score > entry.score || (score == entry.score && mistakes < entry.mistakes)
+            })
+            .unwrap_or(self.entries.len());
+
+        (place < MAX_HIGH_SCORES).then_some(place)
+    }
+
+    /// Add a run to the table - returns its place, or None if it didn't make it
+    pub fn add(&mut self, high_score: HighScore) -> Option<usize> {
+        let place = self.place(high_score.score, high_score.mistakes)?;
+
+        self.entries.insert(place, high_score);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(place)
+    }
+}
+
+// the date in UTC - good enough for a party game
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86_400) as i64;
+
+    // days since 1970-01-01 to a calendar date (Howard Hinnant's civil_from_days)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(Component)]
+struct LeaderboardScreen;
+
+#[derive(Component)]
+struct LeaderboardBackButton;
+
+pub struct HighScoresPlugin;
+
+/// Keeps the high score table, and shows it from the title screen
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        // main puts in the saved table first
+        app.init_resource::<HighScores>()
+            .add_systems(OnEnter(GameState::Leaderboard), setup_leaderboard)
+            .add_systems(
+                Update,
+                click_leaderboard_button.run_if(in_state(GameState::Leaderboard)),
+            )
+            .add_systems(OnExit(GameState::Leaderboard), cleanup_leaderboard);
+    }
+}
+
+fn setup_leaderboard(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    high_scores: Res<HighScores>,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            LeaderboardScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "high scores",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 80.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+
+            if high_scores.entries().is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "no scores yet - go and play!",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 45.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            }
+
+            for (place, entry) in high_scores.entries().iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:}. {:}  {:}  ({:} mistakes)  {:}  {:}",
+                        place + 1,
+                        entry.name,
+                        entry.score,
+                        entry.mistakes,
+                        entry.mode,
+                        entry.date
+                    ),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.0,
+                        // the top spot stands out
+                        color: if place == 0 {
+                            Color::GOLD
+                        } else {
+                            Color::GREEN
+                        },
+                    },
+                ));
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(300.0),
+                            height: Val::Px(50.0),
+                            margin: UiRect::top(Val::Px(20.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..default()
+                    },
+                    LeaderboardBackButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "back",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 60.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+                });
+        });
+}
+
+fn click_leaderboard_button(
+    button_colors: Res<ButtonColors>,
+    mut state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<LeaderboardBackButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => state.set(GameState::TitleScreen),
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn cleanup_leaderboard(
+    mut commands: Commands,
+    screen: Query<Entity, With<LeaderboardScreen>>,
+    background_image: Query<Entity, With<BackgroundImage>>,
+) {
+    for entity in screen.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // the title screen background was left up for this screen
+    for entity in background_image.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
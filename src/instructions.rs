@@ -1,3 +1,4 @@
+use crate::playlist::Playlist;
 use crate::BackgroundImage;
 use crate::ButtonColors;
 use crate::GameState;
@@ -8,6 +9,7 @@ struct InstructionsText {
     screen_state_to_display: InstructionScreenEnum,
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Resource, Default, PartialEq)]
 enum InstructionScreenEnum {
     #[default]
@@ -25,7 +27,10 @@ pub struct InstructionsPlugin;
 impl Plugin for InstructionsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InstructionScreenState>()
-            .add_systems(OnEnter(GameState::Instructions), setup_instructions)
+            .add_systems(
+                OnEnter(GameState::Instructions),
+                (reset_instruction_screen, setup_instructions),
+            )
             .add_systems(
                 Update,
                 update_instructions.run_if(in_state(GameState::Instructions)),
@@ -34,6 +39,11 @@ impl Plugin for InstructionsPlugin {
     }
 }
 
+// start from the first page every time (play again goes back through here)
+fn reset_instruction_screen(mut instruction_screen: ResMut<InstructionScreenState>) {
+    *instruction_screen = InstructionScreenState::default();
+}
+
 fn setup_instructions(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -132,6 +142,7 @@ fn update_instructions(
     button_colors: Res<ButtonColors>,
     mut state: ResMut<NextState<GameState>>,
     mut instruction_screen: ResMut<InstructionScreenState>,
+    mut playlist: ResMut<Playlist>,
     mut button_text_query: Query<&mut Text, Without<InstructionsText>>,
     mut instructions_text_query: Query<(&mut Visibility, &InstructionsText)>,
     mut interaction_query: Query<
@@ -153,7 +164,10 @@ fn update_instructions(
                 }
 
                 InstructionScreenEnum::ScreenThree => {
-                    state.set(GameState::PuzzleTurkeyMistletoe);
+                    state.set(match playlist.start() {
+                        Some(_) => GameState::Puzzle,
+                        None => GameState::ShowScore,
+                    });
                 }
             },
             Interaction::Hovered => {
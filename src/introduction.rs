@@ -1,5 +1,10 @@
+use crate::difficulty::{CustomDifficulty, Difficulty, DifficultySettings};
+use crate::players::Players;
+use crate::playlist::Playlist;
+use crate::replay::{LastRun, WatchReplay};
 use crate::BackgroundImage;
 use crate::GameState;
+use crate::SettingsState;
 use crate::SoundEvent;
 use crate::SoundsEnum;
 use bevy::prelude::*;
@@ -7,6 +12,27 @@ use bevy::prelude::*;
 #[derive(Component)]
 struct TitleText;
 
+// which set of rounds (or other screen) a title screen button leads to
+#[derive(Component, Clone, Copy)]
+enum PlayButton {
+    FullGame,
+    QuickGame,
+    StageSelect,
+    HotSeat,
+    Leaderboard,
+}
+
+// steps through the difficulty presets
+#[derive(Component)]
+struct DifficultyButton;
+
+#[derive(Component)]
+struct SettingsButton;
+
+// only there once a run has been played to the end
+#[derive(Component)]
+struct ReplayButton;
+
 #[derive(Resource)]
 struct ButtonColors {
     normal: Color,
@@ -32,7 +58,13 @@ impl Plugin for IntroductionPlugin {
             .add_systems(OnEnter(GameState::TitleScreen), setup_title_screen)
             .add_systems(
                 Update,
-                click_play_button.run_if(in_state(GameState::TitleScreen)),
+                (
+                    click_play_button,
+                    click_difficulty_button,
+                    click_settings_button,
+                    click_replay_button,
+                )
+                    .run_if(in_state(GameState::TitleScreen)),
             )
             .add_systems(OnExit(GameState::TitleScreen), cleanup_title_screen);
     }
@@ -42,25 +74,30 @@ fn setup_title_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     button_colors: Res<ButtonColors>,
+    difficulty: Res<Difficulty>,
+    last_run: Res<LastRun>,
     mut sound_event_writer: EventWriter<SoundEvent>,
 ) {
     commands
-        .spawn(ButtonBundle {
-            style: Style {
-                width: Val::Px(300.0),
-                height: Val::Px(50.0),
-                margin: UiRect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                left: Val::Px(00.0),
-                right: Val::Px(200.0),
-                top: Val::Px(325.0),
-                bottom: Val::Px(400.0),
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Auto),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(00.0),
+                    right: Val::Px(200.0),
+                    top: Val::Px(325.0),
+                    bottom: Val::Px(400.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
-            ..Default::default()
-        })
+            PlayButton::FullGame,
+        ))
         .with_children(|parent| {
             parent.spawn(
                 TextBundle::from_section(
@@ -75,6 +112,237 @@ fn setup_title_screen(
             );
         });
 
+    // a shorter set of rounds - sits to the right of the main play button
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(1050.0),
+                    top: Val::Px(700.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            PlayButton::QuickGame,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "quick game",
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 50.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    // pick a single round to practice - sits to the left of the main play button
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(300.0),
+                    top: Val::Px(700.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            PlayButton::StageSelect,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "stage select",
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 50.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    // take turns at each round with 2-8 players - far left
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(240.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(30.0),
+                    top: Val::Px(700.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            PlayButton::HotSeat,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "hot-seat",
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 50.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    // the high score table - far right
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(1320.0),
+                    top: Val::Px(700.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            PlayButton::Leaderboard,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "high scores",
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 50.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    // top right, out of the way of the title
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(1270.0),
+                    top: Val::Px(30.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            DifficultyButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    difficulty_label(&difficulty),
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 45.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(300.0),
+                    height: Val::Px(50.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(1270.0),
+                    top: Val::Px(90.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
+                ..Default::default()
+            },
+            SettingsButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "settings",
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: 45.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+
+    if last_run.replay.is_some() {
+        commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        left: Val::Px(1270.0),
+                        top: Val::Px(150.0),
+                        ..Default::default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..Default::default()
+                },
+                ReplayButton,
+            ))
+            .with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(
+                        "watch last run",
+                        TextStyle {
+                            font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                            font_size: 45.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+            });
+    }
+
     commands.spawn((
         SpriteBundle {
             texture: asset_server.load("snowglobe/text/title.png"),
@@ -111,21 +379,120 @@ fn setup_title_screen(
         BackgroundImage,
     ));
 
-    sound_event_writer.send(SoundEvent(SoundsEnum::TitleScreen));
+    sound_event_writer.send(SoundEvent::new(SoundsEnum::TitleScreen));
 }
 
 fn click_play_button(
     button_colors: Res<ButtonColors>,
     mut state: ResMut<NextState<GameState>>,
+    mut playlist: ResMut<Playlist>,
+    mut players: ResMut<Players>,
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+        (&Interaction, &mut BackgroundColor, &PlayButton),
         (Changed<Interaction>, With<Button>),
     >,
+) {
+    for (interaction, mut color, play_button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                // everything but hot-seat is played as one team
+                *players = Players::default();
+
+                match play_button {
+                    PlayButton::FullGame => {
+                        *playlist = Playlist::full();
+                        state.set(GameState::Instructions);
+                    }
+                    PlayButton::QuickGame => {
+                        *playlist = Playlist::quick();
+                        state.set(GameState::Instructions);
+                    }
+                    PlayButton::StageSelect => state.set(GameState::StageSelect),
+                    PlayButton::HotSeat => state.set(GameState::PlayerSetup),
+                    PlayButton::Leaderboard => state.set(GameState::Leaderboard),
+                }
+            }
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn difficulty_label(difficulty: &Difficulty) -> String {
+    format!("difficulty: {}", difficulty.preset().name())
+}
+
+fn click_difficulty_button(
+    button_colors: Res<ButtonColors>,
+    mut difficulty: ResMut<Difficulty>,
+    custom_difficulty: Res<CustomDifficulty>,
+    difficulty_settings: Res<Assets<DifficultySettings>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &Children),
+        (Changed<Interaction>, With<DifficultyButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    for (interaction, mut color, children) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let preset = difficulty.preset().next();
+                difficulty.set(preset, difficulty_settings.get(&custom_difficulty.0));
+
+                let mut text = text_query.get_mut(children[0]).unwrap();
+                text.sections[0].value = difficulty_label(&difficulty);
+            }
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+// the settings open over the title screen
+fn click_settings_button(
+    button_colors: Res<ButtonColors>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SettingsButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => next_settings_state.set(SettingsState::Open),
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn click_replay_button(
+    mut commands: Commands,
+    button_colors: Res<ButtonColors>,
+    last_run: Res<LastRun>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ReplayButton>),
+    >,
 ) {
     for (interaction, mut color) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
-                state.set(GameState::Instructions);
+                if let Some(replay) = &last_run.replay {
+                    commands.insert_resource(WatchReplay(replay.clone()));
+                }
             }
             Interaction::Hovered => {
                 *color = button_colors.hovered.into();
@@ -139,10 +506,12 @@ fn click_play_button(
 
 fn cleanup_title_screen(
     mut commands: Commands,
-    button: Query<Entity, With<Button>>,
+    buttons: Query<Entity, With<Button>>,
     text: Query<Entity, With<TitleText>>,
 ) {
-    commands.entity(button.single()).despawn_recursive();
+    for button in buttons.iter() {
+        commands.entity(button).despawn_recursive();
+    }
     commands.entity(text.single()).despawn_recursive();
     // don't despawn the background image so it shows up in the instructions page (the next game state)
 }
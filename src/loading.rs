@@ -0,0 +1,300 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+
+use crate::audio::SoundRegistry;
+use crate::difficulty::CUSTOM_DIFFICULTY_FILE;
+use crate::picking::{LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::puzzle::PuzzleRegistry;
+use crate::puzzle_definition::{PuzzleDefinition, PuzzleDefinitions};
+use crate::ButtonColors;
+use crate::GameState;
+
+const FONT: &str = "snowglobe/fonts/MTF Dear Santa.ttf";
+
+// the artwork and fonts of the menus and the shared screens - the rounds' own assets are
+// found through the puzzle registry, the puzzle definitions and the sound registry
+const MENU_ASSETS: [&str; 11] = [
+    FONT,
+    "snowglobe/fonts/arialceb.ttf",
+    "snowglobe/text/title.png",
+    "snowglobe/backgrounds/title screen.png",
+    "snowglobe/backgrounds/snowflake - white.png",
+    "snowglobe/text/instructions 1.png",
+    "snowglobe/text/instructions 2.png",
+    "snowglobe/text/instructions 3.png",
+    "snowglobe/text/show score.png",
+    "snowglobe/icons/element background.png",
+    "snowglobe/icons/grocery.png",
+];
+
+/// Everything the game uses, loaded up front - the handles are kept so nothing is unloaded
+/// between one screen and the next
+#[derive(Resource, Default)]
+struct PreloadedAssets {
+    // with the path, to say which one it was if it fails
+    assets: Vec<(String, HandleUntyped)>,
+    // the tiles, slots and decorations of the puzzle definitions have been added
+    definition_artwork_added: bool,
+    // loading is over but some of it failed, and the player has been told which
+    failures_shown: bool,
+}
+
+impl PreloadedAssets {
+    fn add(&mut self, asset_server: &AssetServer, path: &str) {
+        if !self.assets.iter().any(|(added, _)| added == path) {
+            self.assets
+                .push((path.to_owned(), asset_server.load_untyped(path)));
+        }
+    }
+}
+
+// finished one way or another
+fn is_done(load_state: LoadState) -> bool {
+    matches!(load_state, LoadState::Loaded | LoadState::Failed)
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct ContinueButton;
+
+pub struct LoadingPlugin;
+
+/// Waits for every texture, font and sound before the title screen, with a progress bar
+/// (and the snow falling). Anything that couldn't be loaded is listed before carrying on
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PreloadedAssets>()
+            .add_systems(
+                OnEnter(GameState::Loading),
+                (start_loading, setup_loading_screen),
+            )
+            .add_systems(
+                Update,
+                (track_loading, click_continue_button).run_if(in_state(GameState::Loading)),
+            )
+            .add_systems(OnExit(GameState::Loading), cleanup_loading_screen);
+    }
+}
+
+fn start_loading(
+    asset_server: Res<AssetServer>,
+    mut preloaded: ResMut<PreloadedAssets>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+    sound_registry: Res<SoundRegistry>,
+) {
+    for path in MENU_ASSETS {
+        preloaded.add(&asset_server, path);
+    }
+
+    for (_, stage) in puzzle_registry.iter() {
+        preloaded.add(&asset_server, stage.background);
+        preloaded.add(&asset_server, stage.instructions);
+        preloaded.add(&asset_server, stage.answers);
+    }
+
+    for file in puzzle_definitions.files() {
+        preloaded.add(&asset_server, file);
+    }
+    preloaded.add(&asset_server, CUSTOM_DIFFICULTY_FILE);
+
+    for (_, definition) in sound_registry.iter() {
+        preloaded.add(&asset_server, definition.path);
+    }
+}
+
+fn setup_loading_screen(mut commands: Commands, button_colors: Res<ButtonColors>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(600.0),
+                        height: Val::Px(30.0),
+                        border: UiRect::all(Val::Px(3.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.1).into(),
+                    border_color: Color::WHITE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        },
+                        ProgressBar,
+                    ));
+                });
+        });
+}
+
+fn track_loading(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    mut preloaded: ResMut<PreloadedAssets>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+    definition_assets: Res<Assets<PuzzleDefinition>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut progress_bars: Query<&mut Style, With<ProgressBar>>,
+    loading_screens: Query<Entity, With<LoadingScreen>>,
+) {
+    // the puzzle definitions say which artwork their tiles use - so that can only be added
+    // once they have been read
+    if !preloaded.definition_artwork_added
+        && puzzle_definitions
+            .files()
+            .all(|file| is_done(asset_server.get_load_state(file)))
+    {
+        for (_, definition) in definition_assets.iter() {
+            for path in definition.artwork() {
+                preloaded.add(&asset_server, path);
+            }
+        }
+        preloaded.definition_artwork_added = true;
+    }
+
+    let done = preloaded
+        .assets
+        .iter()
+        .filter(|(_, handle)| is_done(asset_server.get_load_state(handle)))
+        .count();
+    for mut style in progress_bars.iter_mut() {
+        style.width = Val::Percent(100.0 * done as f32 / preloaded.assets.len().max(1) as f32);
+    }
+
+    if !preloaded.definition_artwork_added || done < preloaded.assets.len() {
+        return;
+    }
+
+    let failed: Vec<&str> = preloaded
+        .assets
+        .iter()
+        .filter(|(_, handle)| asset_server.get_load_state(handle) == LoadState::Failed)
+        .map(|(path, _)| path.as_str())
+        .collect();
+    if failed.is_empty() {
+        next_state.set(GameState::TitleScreen);
+        return;
+    }
+    if preloaded.failures_shown {
+        return;
+    }
+
+    // rather than carrying on to blank sprites and silence without a word
+    for path in &failed {
+        error!("couldn't load {}", path);
+    }
+
+    // bevy's own font if ours is one of the failures
+    let font = if failed.contains(&FONT) {
+        Handle::default()
+    } else {
+        asset_server.load(FONT)
+    };
+    let list = format!("couldn't load:\n{}", failed.join("\n"));
+    let Ok(loading_screen) = loading_screens.get_single() else {
+        return;
+    };
+    commands.entity(loading_screen).with_children(|parent| {
+        parent.spawn(
+            TextBundle::from_section(
+                list,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_text_alignment(TextAlignment::Center)
+            .with_style(Style {
+                margin: UiRect::vertical(Val::Px(20.0)),
+                ..default()
+            }),
+        );
+
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(50.0),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..default()
+                },
+                ContinueButton,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "continue anyway",
+                    TextStyle {
+                        font,
+                        font_size: 40.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    });
+    preloaded.failures_shown = true;
+}
+
+fn click_continue_button(
+    button_colors: Res<ButtonColors>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ContinueButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => next_state.set(GameState::TitleScreen),
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn cleanup_loading_screen(
+    mut commands: Commands,
+    loading_screens: Query<Entity, With<LoadingScreen>>,
+) {
+    for entity in loading_screens.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,52 +1,90 @@
-use bevy::{prelude::*, window::PresentMode};
+// bevy systems naturally take lots of queries and resources
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::env;
+use std::path::Path;
+use std::time::Duration;
 
 mod audio;
-mod cranberries;
-mod family_tree;
+mod config;
+mod difficulty;
+mod drag_and_drop;
+mod gamepad;
 mod grocery;
+mod high_scores;
 mod instructions;
 mod introduction;
+mod loading;
+mod palette;
+mod pause;
 mod periodic_table;
-mod reindeer;
+mod picking;
+mod players;
+mod playlist;
+mod pointer;
+mod puzzle;
+mod puzzle_definition;
+mod replay;
 mod score;
+mod settings;
 mod show_score;
 mod snowflakes;
 mod stage_common;
+mod stage_select;
+#[cfg(test)]
+mod testing;
 mod timer_bar;
-mod turkey_mistletoe;
-mod twelve_days;
-mod waltz;
+mod tween;
 
 use crate::audio::AudioPlugin;
-use crate::cranberries::CranberriesPlugin;
-use crate::family_tree::FamilyTreePlugin;
-use crate::grocery::GroceryPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::drag_and_drop::DragAndDropPlugin;
+use crate::gamepad::GamepadPlugin;
+use crate::grocery::GroceryPuzzle;
+use crate::high_scores::{HighScores, HighScoresPlugin};
 use crate::instructions::InstructionsPlugin;
 use crate::introduction::IntroductionPlugin;
-use crate::periodic_table::PeriodicTablePlugin;
-use crate::reindeer::ReindeerPlugin;
+use crate::loading::LoadingPlugin;
+use crate::palette::Palette;
+use crate::pause::PausePlugin;
+use crate::periodic_table::PeriodicTablePuzzle;
+use crate::picking::{main_camera, ui_camera, PickingPlugin};
+use crate::players::PlayersPlugin;
+use crate::playlist::Playlist;
+use crate::pointer::PointerPlugin;
+use crate::puzzle::{AddSnowglobePuzzle, PuzzleId};
+use crate::puzzle_definition::PuzzleDefinitionPlugin;
+use crate::replay::{LastRun, Replay, ReplayPlugin, WatchReplay};
 use crate::score::ScorePlugin;
+use crate::settings::{Settings, SettingsPlugin};
 use crate::show_score::ShowScorePlugin;
 use crate::snowflakes::SnowflakesPlugin;
 use crate::stage_common::StageCommonPlugin;
+use crate::stage_select::StageSelectPlugin;
 use crate::timer_bar::TimerBarPlugin;
-use crate::turkey_mistletoe::TurkeyMistletoePlugin;
-use crate::twelve_days::TwelveDaysPlugin;
-use crate::waltz::WaltzPlugin;
+use crate::tween::TweenPlugin;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 enum GameState {
+    // waiting for everything to load - see loading.rs
     #[default]
+    Loading,
     TitleScreen,
     Instructions,
-    PuzzleTurkeyMistletoe,
-    PuzzlePeriodicTable,
-    PuzzleTwelveDays,
-    PuzzleReindeer,
-    PuzzleWaltz,
-    PuzzleGrocery,
-    PuzzleFamilyTree,
-    PuzzleCranberries,
+    StageSelect,
+    // the high score table, from the title screen
+    Leaderboard,
+    // naming the players of a hot-seat game
+    PlayerSetup,
+    // the current round of the playlist is being played
+    Puzzle,
+    // passed through on the way from one round to the next, so the stage is torn down and set up again
+    NextPuzzle,
+    // passed through when a round is restarted from the pause menu
+    RestartPuzzle,
     ShowScore,
 }
 
@@ -58,6 +96,22 @@ enum PuzzleState {
     Complete,
 }
 
+// only ever paused during a round - see pause.rs
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+// the settings screen, over the title screen or the pause menu - see settings.rs
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
 #[derive(Resource)]
 struct ButtonColors {
     normal: Color,
@@ -76,86 +130,170 @@ impl Default for ButtonColors {
 #[derive(Component)]
 struct BackgroundImage;
 
+// why the score changed - for the breakdown on the final score screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScoreReason {
+    TimeBonus,
+    WrongTile,
+    WrongElement,
+    WrongClick,
+}
+
+#[derive(Clone)]
 struct ScoreChange {
     delta_score: f32,
     initial_score: f32,
+    puzzle: PuzzleId,
+    reason: ScoreReason,
+    // how far into the round it happened
+    timestamp: Duration,
+}
+
+// a "+1234" / "-1250" that floats up from where the score changed
+struct ScorePopup {
+    delta_score: f32,
+    position: Vec2,
 }
 
 #[derive(Resource, Default)]
 struct Score {
     current_score: f32,
     mistakes: u32,
-    score_change_vector: Vec<ScoreChange>,
+    // waiting to be counted up (or down) on screen, oldest first
+    score_change_queue: VecDeque<ScoreChange>,
+    // seconds into counting up the change at the front of the queue
+    score_change_elapsed: f32,
+    // every change made this run, oldest first
+    history: Vec<ScoreChange>,
+    popups: Vec<ScorePopup>,
 }
 
-#[derive(Component, PartialEq, Debug)]
+// each one is added to the SoundRegistry (see audio.rs) with its file, bus and loop mode
+#[derive(Component, PartialEq, Eq, Hash, Debug, Clone, Copy, Deserialize)]
 pub enum SoundsEnum {
-    TitleScreen = 0,
-    HoHoHo = 1,
-    Success = 2,
-    Failure = 3,
-    TurkeyMistletoeSong = 4,
-    PeriodicTableSong = 5,
-    TwelveDaysSong = 6,
-    ReindeerSong = 7,
-    WaltzSong = 8,
-    GrocerySong = 9,
-    FamilyTreeSong = 10,
-    CranberriesSong = 11,
-    MerryChristmas = 12,
+    TitleScreen,
+    HoHoHo,
+    Success,
+    Failure,
+    TurkeyMistletoeSong,
+    PeriodicTableSong,
+    TwelveDaysSong,
+    ReindeerSong,
+    WaltzSong,
+    GrocerySong,
+    FamilyTreeSong,
+    CranberriesSong,
+    MerryChristmas,
 }
 
 // events
 #[derive(Event)]
-struct SoundEvent(SoundsEnum);
+struct SoundEvent {
+    sound: SoundsEnum,
+    // on top of the music or effects volume - full volume if None
+    volume: Option<f32>,
+    // how long it takes to fade in (and for a song, how long the one it replaces takes to
+    // fade out) - songs crossfade and effects start straight away if None
+    fade: Option<Duration>,
+}
+
+impl SoundEvent {
+    fn new(sound: SoundsEnum) -> Self {
+        SoundEvent {
+            sound,
+            volume: None,
+            fade: None,
+        }
+    }
+}
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "the christmas snowglobe 3".into(),
-                resolution: (1600., 800.).into(),
-                present_mode: PresentMode::AutoVsync,
-                // Tells wasm to resize the window according to the available canvas
-                fit_canvas_to_parent: true,
-                // Tells wasm not to override default event handling, like F5, Ctrl+R etc.
-                prevent_default_event_handling: false,
-                ..default()
-            }),
+    let settings = Settings::load();
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "the christmas snowglobe 3".into(),
+            resolution: (1600., 800.).into(),
+            present_mode: settings.present_mode(),
+            mode: settings.window_mode(),
+            // Tells wasm to resize the window according to the available canvas
+            fit_canvas_to_parent: true,
+            // Tells wasm not to override default event handling, like F5, Ctrl+R etc.
+            prevent_default_event_handling: false,
             ..default()
-        }))
-        .init_resource::<ButtonColors>()
-        .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(Score {
-            current_score: 0.0,
-            mistakes: 0,
-            score_change_vector: Vec::new(),
-        })
-        .add_state::<GameState>()
-        .add_state::<PuzzleState>()
-        .add_plugins((
-            IntroductionPlugin,
-            SnowflakesPlugin,
-            ScorePlugin,
-            StageCommonPlugin,
-            TurkeyMistletoePlugin,
-            TwelveDaysPlugin,
-            TimerBarPlugin,
-            InstructionsPlugin,
-            AudioPlugin,
-            PeriodicTablePlugin,
-            ReindeerPlugin,
-            WaltzPlugin,
-            GroceryPlugin,
-            FamilyTreePlugin,
-            CranberriesPlugin,
-        ))
-        .add_plugins(ShowScorePlugin) // not sure why this needs to be separate
-        .add_systems(Startup, setup)
-        .add_event::<SoundEvent>()
-        .run();
+        }),
+        ..default()
+    }))
+    .insert_resource(settings)
+    .insert_resource(HighScores::load())
+    .insert_resource(LastRun::load())
+    .add_plugins(SnowglobePlugin);
+
+    // snowglobe --replay <file> watches a replay as soon as the title screen is reached
+    let args: Vec<String> = env::args().collect();
+    if let Some(path) = args
+        .iter()
+        .position(|arg| arg == "--replay")
+        .and_then(|index| args.get(index + 1))
+    {
+        match Replay::load(Path::new(path)) {
+            Ok(replay) => {
+                app.insert_resource(WatchReplay(replay));
+            }
+            Err(error) => error!("couldn't read the replay {}: {}", path, error),
+        }
+    }
+
+    app.run();
+}
+
+/// The whole game, apart from bevy's own plugins and the window - the tests run it headless,
+/// see testing.rs
+struct SnowglobePlugin;
+
+impl Plugin for SnowglobePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ButtonColors>()
+            .init_resource::<Playlist>()
+            .insert_resource(ClearColor(Color::BLACK))
+            .init_resource::<Score>()
+            .init_resource::<Palette>()
+            // main puts in the saved settings (and the high scores and the last run's replay)
+            // first - these are the defaults for the tests
+            .init_resource::<Settings>()
+            .add_state::<GameState>()
+            .add_state::<PuzzleState>()
+            .add_state::<PauseState>()
+            .add_state::<SettingsState>()
+            .add_plugins((PuzzleDefinitionPlugin, DifficultyPlugin, TweenPlugin))
+            .add_plugins((GamepadPlugin, PointerPlugin, PickingPlugin))
+            .add_plugins((
+                IntroductionPlugin,
+                SnowflakesPlugin,
+                ScorePlugin,
+                StageCommonPlugin,
+                DragAndDropPlugin,
+                TimerBarPlugin,
+                InstructionsPlugin,
+                StageSelectPlugin,
+                PlayersPlugin,
+                AudioPlugin,
+                PausePlugin,
+                HighScoresPlugin,
+                SettingsPlugin,
+                LoadingPlugin,
+                ReplayPlugin,
+            ))
+            .add_snowglobe_puzzle(PeriodicTablePuzzle)
+            .add_snowglobe_puzzle(GroceryPuzzle)
+            .add_plugins(ShowScorePlugin) // not sure why this needs to be separate
+            .add_systems(Startup, setup)
+            .add_event::<SoundEvent>();
+    }
 }
 
 fn setup(mut commands: Commands) {
-    commands.spawn(Camera2dBundle::default());
+    commands.spawn(main_camera());
+    commands.spawn(ui_camera());
 }
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum ColorblindMode {
+    #[default]
+    Off,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorblindMode {
+    // the order the settings button steps through them
+    pub fn next(self) -> Self {
+        match self {
+            ColorblindMode::Off => ColorblindMode::Deuteranopia,
+            ColorblindMode::Deuteranopia => ColorblindMode::Protanopia,
+            ColorblindMode::Protanopia => ColorblindMode::Tritanopia,
+            ColorblindMode::Tritanopia => ColorblindMode::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorblindMode::Off => "normal",
+            ColorblindMode::Deuteranopia => "deuteranopia",
+            ColorblindMode::Protanopia => "protanopia",
+            ColorblindMode::Tritanopia => "tritanopia",
+        }
+    }
+}
+
+/// The colors right and wrong answers (and gains and losses) are shown in. Anything red or green
+/// that means something should come from here rather than being picked directly. The mode
+/// is chosen in the settings, and kept up to date with them
+#[derive(Resource, Debug, Default)]
+pub struct Palette {
+    pub mode: ColorblindMode,
+}
+
+impl Palette {
+    /// A right answer, or points gained
+    pub fn good(&self) -> Color {
+        match self.mode {
+            ColorblindMode::Off => Color::GREEN,
+            // sky blue and orange can be told apart whichever of red or green is missing
+            ColorblindMode::Deuteranopia => Color::rgb(0.34, 0.71, 0.91),
+            ColorblindMode::Protanopia => Color::rgb(0.34, 0.71, 0.91),
+            ColorblindMode::Tritanopia => Color::rgb(0.0, 0.75, 0.7),
+        }
+    }
+
+    /// A wrong answer, or points lost
+    pub fn bad(&self) -> Color {
+        match self.mode {
+            ColorblindMode::Off => Color::RED,
+            ColorblindMode::Deuteranopia => Color::rgb(0.9, 0.62, 0.0),
+            // red looks almost black without red cones - yellow stays bright
+            ColorblindMode::Protanopia => Color::rgb(0.94, 0.89, 0.26),
+            ColorblindMode::Tritanopia => Color::rgb(1.0, 0.3, 0.45),
+        }
+    }
+
+    /// A color from a puzzle definition, swapped for its colorblind alternative if it has one
+    pub fn puzzle_color(&self, color: Color, colorblind_color: Option<Color>) -> Color {
+        match self.mode {
+            ColorblindMode::Off => color,
+            _ => colorblind_color.unwrap_or(color),
+        }
+    }
+}
+
+// a mark's strokes, as a fraction of its size from its middle
+const CHECK_STROKES: [(Vec2, Vec2); 2] = [
+    (Vec2::new(-0.45, 0.05), Vec2::new(-0.15, -0.3)),
+    (Vec2::new(-0.15, -0.3), Vec2::new(0.45, 0.4)),
+];
+const CROSS_STROKES: [(Vec2, Vec2); 2] = [
+    (Vec2::new(-0.4, 0.4), Vec2::new(0.4, -0.4)),
+    (Vec2::new(-0.4, -0.4), Vec2::new(0.4, 0.4)),
+];
+
+/// A check (or a cross for a wrong answer) drawn over an answer, so it doesn't come down to color
+pub fn spawn_answer_mark(parent: &mut ChildBuilder, correct: bool, size: f32, position: Vec3) {
+    let strokes = if correct {
+        CHECK_STROKES
+    } else {
+        CROSS_STROKES
+    };
+
+    for (start, end) in strokes {
+        let (start, end) = (start * size, end * size);
+        let along = end - start;
+
+        parent.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: Color::BLACK,
+                custom_size: Some(Vec2::new(along.length(), size * 0.15)),
+                ..default()
+            },
+            transform: Transform {
+                translation: position + ((start + end) / 2.0).extend(0.0),
+                rotation: Quat::from_rotation_z(along.y.atan2(along.x)),
+                ..default()
+            },
+            ..default()
+        });
+    }
+}
@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::picking::{LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::players::Players;
+use crate::ButtonColors;
+use crate::GameState;
+use crate::PauseState;
+use crate::PuzzleState;
+use crate::Score;
+use crate::SettingsState;
+
+#[derive(Component)]
+struct PauseMenu;
+
+// a sound the pause menu stopped, to be started again when it closes - and nothing else
+#[derive(Component)]
+struct PausedByMenu;
+
+#[derive(Component)]
+enum PauseButton {
+    Resume,
+    RestartRound,
+    Settings,
+    QuitToTitle,
+}
+
+/// The score as it was when the current round started, so a restart doesn't keep
+/// whatever was won or lost in the abandoned attempt
+#[derive(Resource, Default)]
+struct RoundStartScore {
+    current_score: f32,
+    mistakes: u32,
+    history_length: usize,
+}
+
+/// Esc toggles the pause menu in this set - anything else that uses esc during a round
+/// runs before it and clears the key press if it has dealt with it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PauseToggle;
+
+pub struct PausePlugin;
+
+/// Esc during a round brings up the pause menu - the timer, the puzzle and the music all
+/// stop until it is closed again
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundStartScore>()
+            .add_systems(OnEnter(GameState::Puzzle), remember_round_start_score)
+            .add_systems(
+                Update,
+                (toggle_pause.in_set(PauseToggle), click_pause_button)
+                    .run_if(in_state(GameState::Puzzle)),
+            )
+            .add_systems(OnEnter(PauseState::Paused), setup_pause_menu)
+            .add_systems(OnExit(PauseState::Paused), cleanup_pause_menu)
+            .add_systems(OnExit(GameState::Puzzle), unpause)
+            .add_systems(OnEnter(GameState::RestartPuzzle), restart_puzzle);
+    }
+}
+
+fn remember_round_start_score(mut score: ResMut<Score>, mut round_start: ResMut<RoundStartScore>) {
+    // anything still being counted up from the last round belongs to the last round
+    score.settle();
+    *round_start = RoundStartScore {
+        current_score: score.current_score,
+        mistakes: score.mistakes,
+        history_length: score.history.len(),
+    };
+}
+
+fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_pause_state.set(match pause_state.get() {
+            PauseState::Running => PauseState::Paused,
+            PauseState::Paused => PauseState::Running,
+        });
+    }
+}
+
+fn setup_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    audio_sink_query: Query<(Entity, &AudioSink)>,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    for (entity, audio_sink) in audio_sink_query.iter() {
+        if !audio_sink.is_paused() {
+            audio_sink.pause();
+            commands.entity(entity).insert(PausedByMenu);
+        }
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                // on top of the stage button and the score, and soaks up clicks meant for them
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PauseMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "paused",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 120.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+
+            for (button, label) in [
+                (PauseButton::Resume, "resume"),
+                (PauseButton::RestartRound, "restart round"),
+                (PauseButton::Settings, "settings"),
+                (PauseButton::QuitToTitle, "quit to title"),
+            ] {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(400.0),
+                                height: Val::Px(60.0),
+                                margin: UiRect::top(Val::Px(20.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        },
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                label,
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 50.0,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_text_alignment(TextAlignment::Center),
+                        );
+                    });
+            }
+        });
+}
+
+fn click_pause_button(
+    button_colors: Res<ButtonColors>,
+    round_start: Res<RoundStartScore>,
+    mut score: ResMut<Score>,
+    mut players: ResMut<Players>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &PauseButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                match button {
+                    PauseButton::Resume => next_pause_state.set(PauseState::Running),
+                    PauseButton::RestartRound => {
+                        next_pause_state.set(PauseState::Running);
+
+                        // back to how things stood when the round began
+                        score.clear_pending();
+                        score.current_score = round_start.current_score;
+                        score.mistakes = round_start.mistakes;
+                        score.history.truncate(round_start.history_length);
+
+                        next_puzzle_state.set(PuzzleState::GetReady);
+                        next_game_state.set(GameState::RestartPuzzle);
+                    }
+                    // opens over the pause menu, which stays paused underneath
+                    PauseButton::Settings => next_settings_state.set(SettingsState::Open),
+                    PauseButton::QuitToTitle => {
+                        next_pause_state.set(PauseState::Running);
+                        *score = Score::default();
+                        players.reset_scores();
+
+                        next_puzzle_state.set(PuzzleState::GetReady);
+                        next_game_state.set(GameState::TitleScreen);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn cleanup_pause_menu(
+    mut commands: Commands,
+    pause_menu_query: Query<Entity, With<PauseMenu>>,
+    audio_sink_query: Query<(Entity, &AudioSink), With<PausedByMenu>>,
+) {
+    for entity in pause_menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // anything that was stopped on the way out of the round stays stopped
+    for (entity, audio_sink) in audio_sink_query.iter() {
+        audio_sink.play();
+        commands.entity(entity).remove::<PausedByMenu>();
+    }
+}
+
+// leaving the round always leaves the game unpaused
+fn unpause(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
+
+// the round is torn down on the way in here, and set up again on the way out
+fn restart_puzzle(mut next_game_state: ResMut<NextState<GameState>>) {
+    next_game_state.set(GameState::Puzzle);
+}
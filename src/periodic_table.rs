@@ -1,15 +1,23 @@
 use bevy::prelude::*;
 use bevy::text::BreakLineOn;
 
+use crate::audio::{AddSound, SoundDefinition};
+use crate::difficulty::{Difficulty, MISTAKE_PENALTY};
+use crate::palette::{spawn_answer_mark, Palette};
+use crate::pointer::Pointer;
+use crate::puzzle::{puzzle_is, PuzzleId, PuzzleStage, SnowglobePuzzle};
 use crate::timer_bar::TimerResource;
 use crate::GameState;
+use crate::PauseState;
 use crate::PuzzleState;
 use crate::Score;
-use crate::ScoreChange;
+use crate::ScoreReason;
 use crate::SoundEvent;
 use crate::SoundsEnum;
 
-pub struct PeriodicTablePlugin;
+pub const PERIODIC_TABLE: PuzzleId = PuzzleId("periodic table");
+
+pub struct PeriodicTablePuzzle;
 
 const ELEMENT_SIZE: f32 = 55.;
 const FONT_SIZE: f32 = 20.;
@@ -23,35 +31,47 @@ struct ElementData {
     has_been_selected: bool,
 }
 
-#[derive(Resource)]
-struct CursorPositionResource {
-    cursor_position: Vec2,
-}
-impl Default for CursorPositionResource {
-    fn default() -> Self {
-        Self {
-            cursor_position: Vec2::MAX, // to prevent cursor position from being zero and selecting an element (ugh)
+impl SnowglobePuzzle for PeriodicTablePuzzle {
+    fn id(&self) -> PuzzleId {
+        PERIODIC_TABLE
+    }
+
+    fn stage(&self) -> PuzzleStage {
+        PuzzleStage {
+            name: "periodic table",
+            background: "snowglobe/backgrounds/chemistry.png",
+            instructions: "snowglobe/text/instructions - stage 2.png",
+            answers: "snowglobe/text/answers - stage 2.png",
+            timer_seconds: Some(20),
+            answer_reveal_seconds: 3,
+            song: Some(SoundsEnum::PeriodicTableSong),
         }
     }
-}
 
-impl Plugin for PeriodicTablePlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<CursorPositionResource>()
-            .add_systems(OnEnter(GameState::PuzzlePeriodicTable), setup)
-            .add_systems(
-                OnEnter(PuzzleState::InProgress),
-                begin_periodic_table_puzzle,
-            )
-            .add_systems(
-                Update,
-                play_periodic_table_puzzle.run_if(in_state(GameState::PuzzlePeriodicTable)),
-            )
-            .add_systems(
-                Update,
-                change_colors.run_if(in_state(GameState::PuzzlePeriodicTable)),
-            )
-            .add_systems(OnExit(GameState::PuzzlePeriodicTable), cleanup);
+        app.add_sound(
+            SoundsEnum::PeriodicTableSong,
+            SoundDefinition::song("snowglobe/sounds/periodic table.ogg"),
+        )
+        .add_systems(
+            OnEnter(GameState::Puzzle),
+            setup.run_if(puzzle_is(PERIODIC_TABLE)),
+        )
+        .add_systems(
+            OnEnter(PuzzleState::InProgress),
+            begin_periodic_table_puzzle,
+        )
+        .add_systems(
+            Update,
+            (play_periodic_table_puzzle, change_colors)
+                .run_if(in_state(GameState::Puzzle))
+                .run_if(in_state(PauseState::Running))
+                .run_if(puzzle_is(PERIODIC_TABLE)),
+        )
+        .add_systems(
+            OnExit(GameState::Puzzle),
+            cleanup.run_if(puzzle_is(PERIODIC_TABLE)),
+        );
     }
 }
 
@@ -224,69 +244,82 @@ fn begin_periodic_table_puzzle(
 }
 
 fn play_periodic_table_puzzle(
-    mouse_button_input: Res<Input<MouseButton>>,
-    windows: Query<&Window>,
-    mut cursor_position_resource: ResMut<CursorPositionResource>,
+    mut commands: Commands,
+    pointer: Res<Pointer>,
     mut score: ResMut<Score>,
     mut sound_event_writer: EventWriter<SoundEvent>,
     mut transforms: Query<&mut Transform>,
     mut elements: Query<(Entity, &mut ElementData)>,
-    mut cursor_moved_events: EventReader<CursorMoved>,
     mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
     timer_resource: Res<TimerResource>,
+    difficulty: Res<Difficulty>,
 ) {
-    for ev in cursor_moved_events.iter() {
-        let window = windows.single();
-        let half_window = Vec2::new(window.resolution.width() / 2.0, window.height() / 2.0);
-        cursor_position_resource.cursor_position = ev.position - half_window;
-    }
+    // an easier difficulty can't reach into the neighbouring element
+    let pick_radius = difficulty
+        .tolerance(ELEMENT_SIZE / 2.0)
+        .min(ELEMENT_SIZE / 2.0);
+
+    let Some(cursor) = pointer.world_position() else {
+        return;
+    };
 
-    if mouse_button_input.just_pressed(MouseButton::Left) {
+    // a click, or a finger coming down on the screen
+    if pointer.just_pressed() {
         for (entity, mut element) in elements.iter_mut() {
             let sprite_pos = transforms.get_mut(entity).unwrap().translation;
 
-            let vector_to_sprite = Vec3::new(
-                sprite_pos.x - cursor_position_resource.cursor_position.x,
-                -sprite_pos.y - cursor_position_resource.cursor_position.y,
-                0.0,
-            );
+            let vector_to_sprite = sprite_pos.truncate() - cursor;
 
-            if vector_to_sprite.length() < ELEMENT_SIZE / 2.0 && !element.has_been_selected {
+            if vector_to_sprite.length() < pick_radius && !element.has_been_selected {
                 element.has_been_selected = true;
 
+                // in the top corner, clear of the symbol - and on top of it all
+                commands.entity(entity).with_children(|parent| {
+                    spawn_answer_mark(
+                        parent,
+                        element.is_correct_element,
+                        ELEMENT_SIZE * 0.3,
+                        Vec3::new(ELEMENT_SIZE * 0.3, ELEMENT_SIZE * 0.3, 2.0),
+                    );
+                });
+
                 if element.is_correct_element {
-                    sound_event_writer.send(SoundEvent(SoundsEnum::HoHoHo));
+                    sound_event_writer.send(SoundEvent::new(SoundsEnum::HoHoHo));
                     next_puzzle_state.set(PuzzleState::Complete);
 
                     let time_remaining = (timer_resource.time_remaining.duration()
                         - timer_resource.time_remaining.elapsed())
                     .as_millis() as f32;
 
-                    let current_score = score.current_score;
-                    score.score_change_vector.push(ScoreChange {
-                        initial_score: current_score,
-                        delta_score: time_remaining,
-                    });
+                    score.change(
+                        time_remaining,
+                        PERIODIC_TABLE,
+                        ScoreReason::TimeBonus,
+                        timer_resource.time_remaining.elapsed(),
+                        sprite_pos.truncate(),
+                    );
                 } else {
-                    sound_event_writer.send(SoundEvent(SoundsEnum::Failure));
+                    sound_event_writer.send(SoundEvent::new(SoundsEnum::Failure));
 
-                    let current_score = score.current_score;
-                    score.score_change_vector.push(ScoreChange {
-                        initial_score: current_score,
-                        delta_score: -1250.0,
-                    });
+                    score.change(
+                        -difficulty.penalty(MISTAKE_PENALTY),
+                        PERIODIC_TABLE,
+                        ScoreReason::WrongElement,
+                        timer_resource.time_remaining.elapsed(),
+                        sprite_pos.truncate(),
+                    );
                 }
             }
         }
     }
 }
 
-fn change_colors(mut sprite_query: Query<(&mut Sprite, &ElementData)>) {
+fn change_colors(palette: Res<Palette>, mut sprite_query: Query<(&mut Sprite, &ElementData)>) {
     for (mut sprite, element_data) in sprite_query.iter_mut() {
         if element_data.has_been_selected {
             match element_data.is_correct_element {
-                true => sprite.color = Color::GREEN,
-                false => sprite.color = Color::RED,
+                true => sprite.color = palette.good(),
+                false => sprite.color = palette.bad(),
             }
         }
     }
@@ -296,5 +329,70 @@ fn cleanup(mut commands: Commands, element_data_query: Query<(Entity, &ElementDa
     for (entity, _element_data) in element_data_query.iter() {
         commands.entity(entity).despawn_recursive(); // to get rid of child text
     }
-    commands.remove_resource::<CursorPositionResource>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestGame;
+
+    fn element_position(game: &mut TestGame, correct: bool) -> Vec2 {
+        let mut elements = game.world_mut().query::<(&ElementData, &Transform)>();
+        elements
+            .iter(game.world())
+            .find(|(element, _)| element.is_correct_element == correct)
+            .map(|(_, transform)| transform.translation.truncate())
+            .unwrap()
+    }
+
+    #[test]
+    fn clicking_the_right_element_solves_the_puzzle() {
+        let mut game = TestGame::new();
+        game.play_round(PERIODIC_TABLE);
+
+        let answer = element_position(&mut game, true);
+        game.click(answer);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        assert!(matches!(
+            game.score_changes()[..],
+            [(ScoreReason::TimeBonus, bonus)] if bonus > 0.0
+        ));
+    }
+
+    #[test]
+    fn tapping_the_right_element_solves_the_puzzle() {
+        let mut game = TestGame::new();
+        game.play_round(PERIODIC_TABLE);
+
+        let answer = element_position(&mut game, true);
+        game.touch_down(answer);
+        game.touch_up(answer);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+    }
+
+    #[test]
+    fn clicking_a_wrong_element_costs_a_penalty_once() {
+        let mut game = TestGame::new();
+        game.play_round(PERIODIC_TABLE);
+
+        let wrong = element_position(&mut game, false);
+        game.click(wrong);
+        // it has been crossed out already
+        game.click(wrong);
+        game.update();
+
+        assert_eq!(game.puzzle_state(), PuzzleState::InProgress);
+        assert_eq!(
+            game.score_changes(),
+            vec![(ScoreReason::WrongElement, -MISTAKE_PENALTY)]
+        );
+
+        game.run_out_the_clock();
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        assert_eq!(game.score_changes().len(), 1);
+    }
 }
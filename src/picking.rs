@@ -0,0 +1,230 @@
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::render::view::RenderLayers;
+use bevy::ui::UiSystem;
+use bevy::window::PrimaryWindow;
+
+/// Everything is laid out for a 1600x800 window - bigger or smaller windows show the same
+/// layout scaled to fit, with black bars down the sides or along the top and bottom
+pub const LAYOUT_WIDTH: f32 = 1600.;
+pub const LAYOUT_HEIGHT: f32 = 800.;
+
+#[derive(Component)]
+pub struct MainCamera;
+
+/// The ui node every screen's own nodes are put in - it covers the layout, wherever the
+/// letterbox has put that in the window
+#[derive(Component)]
+pub struct LayoutRoot;
+
+/// Where the layout sits in the window - the corner is in logical window pixels, and the scale
+/// is how many logical window pixels there are to a pixel of the layout
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Letterbox {
+    pub corner: Vec2,
+    pub scale: f32,
+}
+
+impl Default for Letterbox {
+    fn default() -> Self {
+        Letterbox {
+            corner: Vec2::ZERO,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Letterbox {
+    /// From window coordinates to the layout's - both measured down from the top left
+    pub fn window_to_layout(&self, position: Vec2) -> Vec2 {
+        (position - self.corner) / self.scale
+    }
+
+    pub fn layout_to_window(&self, position: Vec2) -> Vec2 {
+        position * self.scale + self.corner
+    }
+}
+
+/// Where a point in the window is in the world the sprites are drawn in (the middle of the
+/// layout is the origin, and y is up) - None if it is off in the black bars
+pub fn window_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    letterbox: &Letterbox,
+    position: Vec2,
+) -> Option<Vec2> {
+    let layout_position = letterbox.window_to_layout(position);
+    let inside = layout_position.x >= 0.0
+        && layout_position.y >= 0.0
+        && layout_position.x <= LAYOUT_WIDTH
+        && layout_position.y <= LAYOUT_HEIGHT;
+
+    // the viewport is measured from its own corner
+    inside
+        .then(|| camera.viewport_to_world_2d(camera_transform, position - letterbox.corner))
+        .flatten()
+}
+
+/// Whether a point in the world is on a sprite's picture - the custom size rectangle, moved,
+/// turned and scaled with the sprite. Sprites without a custom size can't be picked
+pub fn sprite_contains(sprite: &Sprite, transform: &Transform, point: Vec2) -> bool {
+    let Some(size) = sprite.custom_size else {
+        return false;
+    };
+
+    let local = transform
+        .compute_affine()
+        .inverse()
+        .transform_point3(point.extend(transform.translation.z))
+        .truncate();
+    // the anchor is where the transform is on the picture - the middle unless it says otherwise
+    let middle = -sprite.anchor.as_vec() * size;
+
+    let offset = (local - middle).abs();
+    offset.x <= size.x / 2.0 && offset.y <= size.y / 2.0
+}
+
+pub fn main_camera() -> impl Bundle {
+    (
+        Camera2dBundle {
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed {
+                    width: LAYOUT_WIDTH,
+                    height: LAYOUT_HEIGHT,
+                },
+                ..Camera2dBundle::default().projection
+            },
+            ..default()
+        },
+        // the ui has a camera of its own
+        UiCameraConfig { show_ui: false },
+        MainCamera,
+    )
+}
+
+/// Draws the ui over the whole window rather than the letterbox, so the ui and the cursor are
+/// measured from the same corner - the layout root is moved into the letterbox instead
+pub fn ui_camera() -> impl Bundle {
+    (
+        Camera2dBundle {
+            camera: Camera {
+                // after the sprites, and on top of them
+                order: 1,
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            ..default()
+        },
+        // none of the sprites
+        RenderLayers::none(),
+    )
+}
+
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Letterbox>()
+            .add_systems(Startup, spawn_layout_root)
+            .add_systems(PreUpdate, fit_layout_to_window.before(InputSystem))
+            .add_systems(PostUpdate, put_ui_in_layout.before(UiSystem::Layout));
+    }
+}
+
+fn spawn_layout_root(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Px(LAYOUT_WIDTH),
+                height: Val::Px(LAYOUT_HEIGHT),
+                ..default()
+            },
+            ..default()
+        },
+        LayoutRoot,
+    ));
+}
+
+// the screens spawn their nodes without a parent - they are all moved into the layout root
+// before the ui is laid out
+fn put_ui_in_layout(
+    mut commands: Commands,
+    layout_roots: Query<Entity, With<LayoutRoot>>,
+    ui_roots: Query<Entity, (With<Node>, Without<Parent>, Without<LayoutRoot>)>,
+) {
+    let Ok(layout_root) = layout_roots.get_single() else {
+        return;
+    };
+
+    for entity in ui_roots.iter() {
+        commands.entity(layout_root).add_child(entity);
+    }
+}
+
+fn fit_layout_to_window(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut Camera, With<MainCamera>>,
+    mut letterbox: ResMut<Letterbox>,
+    mut ui_scale: ResMut<UiScale>,
+    mut layout_roots: Query<&mut Style, With<LayoutRoot>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    // minimised
+    if window_size.min_element() < 1.0 {
+        return;
+    }
+
+    // worked out in physical pixels so the viewport lands on whole pixels
+    let scale = (window_size.x / LAYOUT_WIDTH).min(window_size.y / LAYOUT_HEIGHT);
+    let size = (Vec2::new(LAYOUT_WIDTH, LAYOUT_HEIGHT) * scale)
+        .round()
+        .max(Vec2::ONE);
+    let corner = ((window_size - size) / 2.0).floor();
+
+    let (physical_position, physical_size) = (corner.as_uvec2(), size.as_uvec2());
+    for mut camera in cameras.iter_mut() {
+        let fitted = camera.viewport.as_ref().is_some_and(|viewport| {
+            viewport.physical_position == physical_position
+                && viewport.physical_size == physical_size
+        });
+        if !fitted {
+            camera.viewport = Some(Viewport {
+                physical_position,
+                physical_size,
+                ..default()
+            });
+        }
+    }
+
+    let scale_factor = window.resolution.scale_factor() as f32;
+    letterbox.set_if_neq(Letterbox {
+        corner: corner / scale_factor,
+        scale: scale / scale_factor,
+    });
+    if ui_scale.scale != letterbox.scale as f64 {
+        ui_scale.scale = letterbox.scale as f64;
+    }
+
+    // the ui is measured in layout pixels, from the corner of the window
+    let (left, top) = (
+        Val::Px(letterbox.corner.x / letterbox.scale),
+        Val::Px(letterbox.corner.y / letterbox.scale),
+    );
+    for mut style in layout_roots.iter_mut() {
+        if style.left != left || style.top != top {
+            style.left = left;
+            style.top = top;
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use crate::picking::{LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::playlist::Playlist;
+use crate::BackgroundImage;
+use crate::ButtonColors;
+use crate::GameState;
+use crate::Score;
+use bevy::prelude::*;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+const MAX_NAME_LENGTH: usize = 12;
+
+pub struct Player {
+    pub name: String,
+    pub score: Score,
+}
+
+/// The players of a hot-seat game - empty when playing as a single team.
+/// Whoever's turn it is keeps their score in the `Score` resource (so the puzzles don't need to
+/// know about any of this), the other players' scores are parked here.
+#[derive(Resource, Default)]
+pub struct Players {
+    players: Vec<Player>,
+    current: usize,
+}
+
+impl Players {
+    /// Players with these names and fresh scores - none for a single team
+    pub fn named(names: &[String]) -> Self {
+        Players {
+            players: names
+                .iter()
+                .map(|name| Player {
+                    name: name.clone(),
+                    score: Score::default(),
+                })
+                .collect(),
+            current: 0,
+        }
+    }
+
+    /// Everyone's name, in turn order
+    pub fn names(&self) -> Vec<String> {
+        self.players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect()
+    }
+
+    pub fn is_hot_seat(&self) -> bool {
+        !self.players.is_empty()
+    }
+
+    /// Whether everyone else has already had a go at this round - always for a single team
+    pub fn is_last_turn(&self) -> bool {
+        self.current + 1 >= self.players.len()
+    }
+
+    pub fn current_name(&self) -> Option<&str> {
+        self.players
+            .get(self.current)
+            .map(|player| player.name.as_str())
+    }
+
+    /// Hand the puzzle over to the next player. Returns false once everyone has had their turn
+    /// at this round - the first player is then up again for the next one.
+    pub fn next_turn(&mut self, score: &mut Score) -> bool {
+        if self.players.is_empty() {
+            return false;
+        }
+
+        score.settle();
+        std::mem::swap(score, &mut self.players[self.current].score);
+
+        self.current = (self.current + 1) % self.players.len();
+        std::mem::swap(score, &mut self.players[self.current].score);
+
+        self.current != 0
+    }
+
+    /// Everyone's name, score and mistakes - best first
+    pub fn standings(&self, score: &Score) -> Vec<(&str, f32, u32)> {
+        let mut standings: Vec<(&str, f32, u32)> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(index, player)| {
+                // the current player's score is the live one
+                let player_score = if index == self.current {
+                    score
+                } else {
+                    &player.score
+                };
+                (
+                    player.name.as_str(),
+                    player_score.current_score,
+                    player_score.mistakes,
+                )
+            })
+            .collect();
+
+        standings.sort_by(|a, b| b.1.total_cmp(&a.1));
+        standings
+    }
+
+    /// Same players, fresh scores
+    pub fn reset_scores(&mut self) {
+        for player in self.players.iter_mut() {
+            player.score = Score::default();
+        }
+        self.current = 0;
+    }
+}
+
+#[derive(Component)]
+struct PlayerSetupScreen;
+
+// the line can be clicked to rename that player
+#[derive(Component)]
+struct PlayerNameText(usize);
+
+/// Which player's name typing goes into
+#[derive(Resource, Default)]
+struct EditedPlayerName(usize);
+
+#[derive(Component)]
+enum PlayerSetupButton {
+    AddPlayer,
+    RemovePlayer,
+    FullGame,
+    QuickGame,
+    Back,
+}
+
+pub struct PlayersPlugin;
+
+/// The hot-seat setup screen - add 2 to 8 players and type their names
+impl Plugin for PlayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Players>()
+            .init_resource::<EditedPlayerName>()
+            .add_systems(OnEnter(GameState::PlayerSetup), setup_player_setup)
+            .add_systems(
+                Update,
+                (
+                    click_player_setup_button,
+                    pick_player_name,
+                    type_player_name,
+                    update_player_names,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::PlayerSetup)),
+            )
+            .add_systems(OnExit(GameState::PlayerSetup), cleanup_player_setup);
+    }
+}
+
+// what a player is called until someone types a name in
+fn placeholder_name(index: usize) -> String {
+    format!("player {}", index + 1)
+}
+
+fn setup_player_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    mut players: ResMut<Players>,
+    mut edited_name: ResMut<EditedPlayerName>,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    *players = Players::default();
+    for index in 0..MIN_PLAYERS {
+        players.players.push(Player {
+            name: placeholder_name(index),
+            score: Score::default(),
+        });
+    }
+    *edited_name = EditedPlayerName(0);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            PlayerSetupScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "who's playing? (click a name or press tab, then type)",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 60.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+
+            // one line per possible player - the unused ones are left empty
+            for index in 0..MAX_PLAYERS {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 50.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                    Interaction::default(),
+                    PlayerNameText(index),
+                ));
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (button, label) in [
+                        (PlayerSetupButton::AddPlayer, "add player"),
+                        (PlayerSetupButton::RemovePlayer, "remove player"),
+                        (PlayerSetupButton::FullGame, "full game"),
+                        (PlayerSetupButton::QuickGame, "quick game"),
+                        (PlayerSetupButton::Back, "back"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(250.0),
+                                        height: Val::Px(50.0),
+                                        margin: UiRect::horizontal(Val::Px(10.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    background_color: button_colors.normal.into(),
+                                    ..default()
+                                },
+                                button,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        label,
+                                        TextStyle {
+                                            font: font.clone(),
+                                            font_size: 40.0,
+                                            color: Color::WHITE,
+                                        },
+                                    )
+                                    .with_text_alignment(TextAlignment::Center),
+                                );
+                            });
+                    }
+                });
+        });
+}
+
+fn click_player_setup_button(
+    mut commands: Commands,
+    button_colors: Res<ButtonColors>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut playlist: ResMut<Playlist>,
+    mut players: ResMut<Players>,
+    mut edited_name: ResMut<EditedPlayerName>,
+    mut score: ResMut<Score>,
+    background_image: Query<Entity, With<BackgroundImage>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &PlayerSetupButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => match button {
+                PlayerSetupButton::AddPlayer => {
+                    if players.players.len() < MAX_PLAYERS {
+                        // typing goes straight into the new player's name
+                        edited_name.0 = players.players.len();
+                        players.players.push(Player {
+                            name: placeholder_name(edited_name.0),
+                            score: Score::default(),
+                        });
+                    }
+                }
+                PlayerSetupButton::RemovePlayer => {
+                    if players.players.len() > MIN_PLAYERS {
+                        players.players.pop();
+                        edited_name.0 = edited_name.0.min(players.players.len() - 1);
+                    }
+                }
+                PlayerSetupButton::FullGame | PlayerSetupButton::QuickGame => {
+                    *playlist = match button {
+                        PlayerSetupButton::QuickGame => Playlist::quick(),
+                        _ => Playlist::full(),
+                    };
+                    players.reset_scores();
+                    *score = Score::default();
+                    next_game_state.set(GameState::Instructions);
+                }
+                PlayerSetupButton::Back => {
+                    *players = Players::default();
+
+                    // the title screen puts up its own background
+                    for entity in background_image.iter() {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                    next_game_state.set(GameState::TitleScreen);
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+// clicking a name, or tab to go down the list (and back round to the top)
+fn pick_player_name(
+    players: Res<Players>,
+    mut edited_name: ResMut<EditedPlayerName>,
+    keys: Res<Input<KeyCode>>,
+    name_text_query: Query<(&Interaction, &PlayerNameText), Changed<Interaction>>,
+) {
+    for (interaction, name_text) in name_text_query.iter() {
+        if *interaction == Interaction::Pressed && name_text.0 < players.players.len() {
+            edited_name.0 = name_text.0;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Tab) && !players.players.is_empty() {
+        edited_name.0 = (edited_name.0 + 1) % players.players.len();
+    }
+}
+
+fn type_player_name(
+    mut players: ResMut<Players>,
+    edited_name: Res<EditedPlayerName>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+) {
+    let index = edited_name.0;
+    let Some(player) = players.players.get_mut(index) else {
+        return;
+    };
+
+    for ev in received_characters.iter() {
+        if ev.char.is_control() {
+            continue;
+        }
+        // the first letter typed replaces "player N" rather than going on the end of it
+        if player.name == placeholder_name(index) {
+            player.name.clear();
+        }
+        if player.name.chars().count() < MAX_NAME_LENGTH {
+            player.name.push(ev.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        player.name.pop();
+    }
+}
+
+fn update_player_names(
+    players: Res<Players>,
+    edited_name: Res<EditedPlayerName>,
+    mut name_text_query: Query<(&mut Text, &PlayerNameText)>,
+) {
+    for (mut text, name_text) in name_text_query.iter_mut() {
+        text.sections[0].value = match players.players.get(name_text.0) {
+            Some(player) => player.name.clone(),
+            None => String::new(),
+        };
+
+        // highlight the name that is being typed into
+        text.sections[0].style.color = if name_text.0 == edited_name.0 {
+            Color::GOLD
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+fn cleanup_player_setup(mut commands: Commands, screen: Query<Entity, With<PlayerSetupScreen>>) {
+    for entity in screen.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
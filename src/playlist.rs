@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::grocery::GROCERY;
+use crate::periodic_table::PERIODIC_TABLE;
+use crate::puzzle::PuzzleId;
+use crate::puzzle_definition::{
+    CRANBERRIES, FAMILY_TREE, REINDEER, TURKEY_MISTLETOE, TWELVE_DAYS, WALTZ,
+};
+
+/// The rounds to play and the order to play them in - the stage complete button walks through this
+#[derive(Resource)]
+pub struct Playlist {
+    // shown with the high scores
+    name: &'static str,
+    puzzles: Vec<PuzzleId>,
+    current: usize,
+}
+
+impl Default for Playlist {
+    fn default() -> Self {
+        Playlist::full()
+    }
+}
+
+impl Playlist {
+    pub fn new(name: &'static str, puzzles: Vec<PuzzleId>) -> Self {
+        Playlist {
+            name,
+            puzzles,
+            current: 0,
+        }
+    }
+
+    // every round, in the original order
+    pub fn full() -> Self {
+        Playlist::new(
+            "full game",
+            vec![
+                TURKEY_MISTLETOE,
+                PERIODIC_TABLE,
+                WALTZ,
+                REINDEER,
+                GROCERY,
+                TWELVE_DAYS,
+                FAMILY_TREE,
+                CRANBERRIES,
+            ],
+        )
+    }
+
+    // a shorter set for when there isn't time for the whole thing (office parties...)
+    pub fn quick() -> Self {
+        Playlist::new(
+            "quick game",
+            vec![TURKEY_MISTLETOE, REINDEER, GROCERY, CRANBERRIES],
+        )
+    }
+
+    /// Go back to the top of the list and return the first round
+    pub fn start(&mut self) -> Option<PuzzleId> {
+        self.current = 0;
+        self.current()
+    }
+
+    /// Move on to the next round - returns None once the list runs out
+    pub fn advance(&mut self) -> Option<PuzzleId> {
+        self.current = (self.current + 1).min(self.puzzles.len());
+        self.current()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn puzzles(&self) -> &[PuzzleId] {
+        &self.puzzles
+    }
+
+    pub fn current(&self) -> Option<PuzzleId> {
+        self.puzzles.get(self.current).copied()
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.puzzles.len()
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::picking::{window_to_world, Letterbox, MainCamera};
+
+/// Where the player is pointing and whether they are pressing - the mouse (or a gamepad's
+/// cursor) and the first finger on a touch screen look the same to the puzzles
+#[derive(Resource, Default, Debug)]
+pub struct Pointer {
+    // in window coordinates, like CursorMoved - None until the mouse has moved or the screen is touched
+    position: Option<Vec2>,
+    // the same point in the world the sprites are in - None when it is off the layout
+    world_position: Option<Vec2>,
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+    // the finger that is doing the pointing
+    touch: Option<u64>,
+}
+
+impl Pointer {
+    pub fn position(&self) -> Option<Vec2> {
+        self.position
+    }
+
+    pub fn world_position(&self) -> Option<Vec2> {
+        self.world_position
+    }
+
+    pub fn pressed(&self) -> bool {
+        self.pressed
+    }
+
+    pub fn just_pressed(&self) -> bool {
+        self.just_pressed
+    }
+
+    pub fn just_released(&self) -> bool {
+        self.just_released
+    }
+}
+
+/// The pointer is brought up to date in here - anything that fakes mouse input runs before it
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerSet;
+
+pub struct PointerPlugin;
+
+impl Plugin for PointerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Pointer>().add_systems(
+            PreUpdate,
+            (update_pointer, locate_pointer_in_world)
+                .chain()
+                .in_set(PointerSet)
+                .after(InputSystem),
+        );
+    }
+}
+
+fn update_pointer(
+    mut pointer: ResMut<Pointer>,
+    mouse_button_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut cursor_moved_events: EventReader<CursorMoved>,
+) {
+    pointer.just_pressed = false;
+    pointer.just_released = false;
+
+    // a finger goes down - it has the pointer until it is lifted
+    if pointer.touch.is_none() {
+        if let Some(touch) = touches.iter_just_pressed().next() {
+            pointer.touch = Some(touch.id());
+            pointer.position = Some(touch.position());
+            pointer.pressed = true;
+            pointer.just_pressed = true;
+        }
+    }
+
+    if let Some(id) = pointer.touch {
+        // a tap can go down and come up again between two frames
+        let lifted = touches
+            .get_released(id)
+            .or_else(|| touches.iter_just_canceled().find(|touch| touch.id() == id));
+
+        if let Some(touch) = lifted {
+            pointer.position = Some(touch.position());
+            pointer.touch = None;
+            pointer.pressed = false;
+            pointer.just_released = true;
+        } else if let Some(touch) = touches.get_pressed(id) {
+            pointer.position = Some(touch.position());
+        } else {
+            // lost track of it
+            pointer.touch = None;
+            pointer.pressed = false;
+        }
+
+        cursor_moved_events.clear();
+        return;
+    }
+
+    if let Some(event) = cursor_moved_events.iter().last() {
+        pointer.position = Some(event.position);
+    }
+
+    pointer.pressed = mouse_button_input.pressed(MouseButton::Left);
+    pointer.just_pressed = mouse_button_input.just_pressed(MouseButton::Left);
+    pointer.just_released = mouse_button_input.just_released(MouseButton::Left);
+}
+
+fn locate_pointer_in_world(
+    mut pointer: ResMut<Pointer>,
+    letterbox: Res<Letterbox>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let world_position = match (pointer.position, cameras.get_single()) {
+        (Some(position), Ok((camera, camera_transform))) => {
+            window_to_world(camera, camera_transform, &letterbox, position)
+        }
+        _ => None,
+    };
+
+    pointer.world_position = world_position;
+}
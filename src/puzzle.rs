@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::playlist::Playlist;
+use crate::SoundsEnum;
+
+/// Identifies a round - used by the playlist and for the run conditions of a puzzle's own systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PuzzleId(pub &'static str);
+
+/// Everything the shared stage, timer and audio plugins need to know about a round
+#[derive(Debug, Clone)]
+pub struct PuzzleStage {
+    // shown on the stage select screen
+    pub name: &'static str,
+    pub background: &'static str,
+    pub instructions: &'static str,
+    pub answers: &'static str,
+    // None when the round's definition file gives it
+    pub timer_seconds: Option<u64>,
+    // the answers are shown once this many seconds are left on the timer
+    pub answer_reveal_seconds: u64,
+    // the puzzle adds it to the SoundRegistry itself, with add_sound - None when the round's
+    // definition file names it
+    pub song: Option<SoundsEnum>,
+}
+
+/// A round of the snowglobe. The shared plugins take care of the background, the
+/// begin/next button, the instructions and answers, the timer and the song - `build`
+/// only has to add the systems that are specific to this puzzle, using `puzzle_is`
+/// (and `in_state(GameState::Puzzle)` for `Update` systems) so they only run for this round.
+pub trait SnowglobePuzzle {
+    fn id(&self) -> PuzzleId;
+    fn stage(&self) -> PuzzleStage;
+    fn build(&self, app: &mut App);
+}
+
+/// Every registered round, in the order they were added
+#[derive(Resource, Default)]
+pub struct PuzzleRegistry {
+    stages: Vec<(PuzzleId, PuzzleStage)>,
+}
+
+impl PuzzleRegistry {
+    pub fn get(&self, id: PuzzleId) -> Option<&PuzzleStage> {
+        self.stages
+            .iter()
+            .find(|(puzzle_id, _)| *puzzle_id == id)
+            .map(|(_, stage)| stage)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(PuzzleId, PuzzleStage)> {
+        self.stages.iter()
+    }
+}
+
+pub trait AddSnowglobePuzzle {
+    fn add_snowglobe_puzzle(&mut self, puzzle: impl SnowglobePuzzle) -> &mut Self;
+}
+
+impl AddSnowglobePuzzle for App {
+    fn add_snowglobe_puzzle(&mut self, puzzle: impl SnowglobePuzzle) -> &mut Self {
+        self.init_resource::<PuzzleRegistry>();
+
+        let mut registry = self.world.resource_mut::<PuzzleRegistry>();
+        if registry.get(puzzle.id()).is_some() {
+            panic!("the puzzle {:?} has already been added", puzzle.id());
+        }
+        registry.stages.push((puzzle.id(), puzzle.stage()));
+
+        puzzle.build(self);
+        self
+    }
+}
+
+/// Run condition - true while the given puzzle is the current round of the playlist
+pub fn puzzle_is(id: PuzzleId) -> impl FnMut(Res<Playlist>) -> bool + Clone {
+    move |playlist: Res<Playlist>| playlist.current() == Some(id)
+}
+
+/// The stage of the current round, if there is one
+pub fn current_stage<'a>(
+    playlist: &Playlist,
+    registry: &'a PuzzleRegistry,
+) -> Option<&'a PuzzleStage> {
+    playlist.current().and_then(|id| registry.get(id))
+}
@@ -0,0 +1,348 @@
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::{TypePath, TypeUuid};
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::Deserialize;
+
+use crate::audio::SoundDefinition;
+use crate::drag_and_drop::{DragAndDropParameters, DragAndDropPuzzle};
+use crate::puzzle::{PuzzleId, PuzzleStage};
+use crate::SoundsEnum;
+
+pub const TURKEY_MISTLETOE: PuzzleId = PuzzleId("turkey mistletoe");
+pub const TWELVE_DAYS: PuzzleId = PuzzleId("twelve days");
+pub const REINDEER: PuzzleId = PuzzleId("reindeer");
+pub const WALTZ: PuzzleId = PuzzleId("waltz");
+pub const FAMILY_TREE: PuzzleId = PuzzleId("family tree");
+pub const CRANBERRIES: PuzzleId = PuzzleId("cranberries");
+
+// the rounds laid out by a description file - these live in assets/snowglobe/puzzles
+// and use the .puzzle.ron extension. The file gives the timer length and names the song,
+// so the songs are only added to the sound registry here
+pub const DRAG_AND_DROP_PUZZLES: [DragAndDropPuzzle; 6] = [
+    DragAndDropPuzzle {
+        id: TURKEY_MISTLETOE,
+        definition_file: "snowglobe/puzzles/turkey mistletoe.puzzle.ron",
+        stage: PuzzleStage {
+            name: "turkey & mistletoe",
+            background: "snowglobe/backgrounds/fireplace.png",
+            instructions: "snowglobe/text/instructions - stage 1.png",
+            answers: "snowglobe/text/answers - stage 1.png",
+            timer_seconds: None,
+            answer_reveal_seconds: 3,
+            song: None,
+        },
+        song: (
+            SoundsEnum::TurkeyMistletoeSong,
+            SoundDefinition::song("snowglobe/sounds/turkey mistletoe.ogg"),
+        ),
+    },
+    DragAndDropPuzzle {
+        id: TWELVE_DAYS,
+        definition_file: "snowglobe/puzzles/twelve days.puzzle.ron",
+        stage: PuzzleStage {
+            name: "twelve days",
+            background: "snowglobe/backgrounds/twelve.png",
+            instructions: "snowglobe/text/instructions - stage 3.png",
+            answers: "snowglobe/text/instructions - stage 3.png", // no answers provided
+            timer_seconds: None,
+            answer_reveal_seconds: 3,
+            song: None,
+        },
+        song: (
+            SoundsEnum::TwelveDaysSong,
+            SoundDefinition::song("snowglobe/sounds/twelve days.ogg").looping(),
+        ),
+    },
+    DragAndDropPuzzle {
+        id: REINDEER,
+        definition_file: "snowglobe/puzzles/reindeer.puzzle.ron",
+        stage: PuzzleStage {
+            name: "reindeer",
+            background: "snowglobe/backgrounds/reindeer.png",
+            instructions: "snowglobe/text/instructions - stage 3.png",
+            answers: "snowglobe/text/instructions - stage 3.png", // no answers provided
+            timer_seconds: None,
+            answer_reveal_seconds: 3,
+            song: None,
+        },
+        song: (
+            SoundsEnum::ReindeerSong,
+            SoundDefinition::song("snowglobe/sounds/reindeer.ogg").looping(),
+        ),
+    },
+    DragAndDropPuzzle {
+        id: WALTZ,
+        definition_file: "snowglobe/puzzles/waltz.puzzle.ron",
+        stage: PuzzleStage {
+            name: "waltz",
+            background: "snowglobe/backgrounds/orchestra.png",
+            instructions: "snowglobe/text/instructions - stage 5.png",
+            answers: "snowglobe/text/answers - stage 5.png",
+            timer_seconds: None,
+            answer_reveal_seconds: 3,
+            song: None,
+        },
+        song: (
+            SoundsEnum::WaltzSong,
+            SoundDefinition::song("snowglobe/sounds/waltz.ogg"),
+        ),
+    },
+    DragAndDropPuzzle {
+        id: FAMILY_TREE,
+        definition_file: "snowglobe/puzzles/family tree.puzzle.ron",
+        stage: PuzzleStage {
+            name: "family tree",
+            background: "snowglobe/backgrounds/family tree.png",
+            instructions: "snowglobe/text/instructions - stage 7.png",
+            answers: "snowglobe/text/answers - stage 7.png",
+            timer_seconds: None,
+            answer_reveal_seconds: 5,
+            song: None,
+        },
+        song: (
+            SoundsEnum::FamilyTreeSong,
+            SoundDefinition::song("snowglobe/sounds/family.ogg").looping(),
+        ),
+    },
+    DragAndDropPuzzle {
+        id: CRANBERRIES,
+        definition_file: "snowglobe/puzzles/cranberries.puzzle.ron",
+        stage: PuzzleStage {
+            name: "cranberries",
+            background: "snowglobe/backgrounds/cranberries.png",
+            instructions: "snowglobe/text/instructions - stage 8.png",
+            answers: "snowglobe/text/answers - stage 8.png",
+            timer_seconds: None,
+            answer_reveal_seconds: 4,
+            song: None,
+        },
+        song: (
+            SoundsEnum::CranberriesSong,
+            SoundDefinition::song("snowglobe/sounds/cranberries.ogg"),
+        ),
+    },
+];
+
+/// Everything needed to run a tile puzzle: how long it lasts, its song, the tiles, the answer
+/// slots they belong in and any extra artwork
+#[derive(Deserialize, TypeUuid, TypePath, Debug)]
+#[uuid = "6f5bd3a2-8f0e-4c8e-9a57-2d1f4d4b3c21"]
+pub struct PuzzleDefinition {
+    pub timer_seconds: u64,
+    // one of the songs added with DRAG_AND_DROP_PUZZLES
+    pub song: SoundsEnum,
+    pub icon_size: Vec2,
+    pub parameters: DragAndDropParameters,
+    #[serde(default)]
+    pub slot_style: SlotStyle,
+    #[serde(default)]
+    pub slots: Vec<SlotDefinition>,
+    pub tiles: Vec<TileDefinition>,
+    #[serde(default)]
+    pub decorations: Vec<DecorationDefinition>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SlotStyle {
+    pub filename: String,
+    pub color: Color,
+    // used instead of color when a colorblind palette is chosen
+    #[serde(default)]
+    pub colorblind_color: Option<Color>,
+    pub font_size: f32,
+}
+
+impl Default for SlotStyle {
+    fn default() -> Self {
+        SlotStyle {
+            filename: "snowglobe/icons/element background.png".to_owned(),
+            color: Color::WHITE,
+            colorblind_color: None,
+            font_size: 100.,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SlotDefinition {
+    pub position: Vec3,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TileDefinition {
+    pub filename: String,
+    pub starting_location: Vec3,
+    pub target: TileTarget,
+}
+
+/// Where a tile has to be dropped to count as placed
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TileTarget {
+    // index into the slots list
+    Slot(usize),
+    // any drop counts (e.g. picking the right items out of a lineup)
+    Anywhere,
+    // a decoy - never correct
+    Nowhere,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DecorationDefinition {
+    pub filename: String,
+    pub position: Vec3,
+    pub size: Vec2,
+    #[serde(default = "default_decoration_color")]
+    pub color: Color,
+}
+
+fn default_decoration_color() -> Color {
+    Color::WHITE
+}
+
+impl PuzzleDefinition {
+    /// The files of every picture the puzzle uses - its slots, tiles and decorations
+    pub fn artwork(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.slot_style.filename.as_str())
+            .chain(self.tiles.iter().map(|tile| tile.filename.as_str()))
+            .chain(
+                self.decorations
+                    .iter()
+                    .map(|decoration| decoration.filename.as_str()),
+            )
+    }
+
+    /// Whatever a file can get wrong that the format doesn't catch - a tile meant for a slot
+    /// that isn't there
+    pub fn check(&self) -> Result<(), String> {
+        for tile in self.tiles.iter() {
+            if let TileTarget::Slot(index) = tile.target {
+                if index >= self.slots.len() {
+                    return Err(format!(
+                        "{} is meant for slot {}, but the file has {} slot(s)",
+                        tile.filename,
+                        index,
+                        self.slots.len()
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The location a tile has to reach - decoys get Vec3::MAX so they can never be placed
+    pub fn correct_location(&self, tile: &TileDefinition) -> Vec3 {
+        match tile.target {
+            TileTarget::Slot(index) => self.slots[index].position,
+            TileTarget::Anywhere | TileTarget::Nowhere => Vec3::MAX,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct PuzzleDefinitions {
+    files: Vec<(PuzzleId, &'static str)>,
+    handles: HashMap<PuzzleId, Handle<PuzzleDefinition>>,
+}
+
+impl PuzzleDefinitions {
+    /// Remember which file describes a puzzle - it gets loaded at startup
+    pub fn add(&mut self, id: PuzzleId, filename: &'static str) {
+        self.files.push((id, filename));
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.files.iter().map(|(_, filename)| *filename)
+    }
+
+    pub fn contains(&self, id: PuzzleId) -> bool {
+        self.files.iter().any(|(puzzle_id, _)| *puzzle_id == id)
+    }
+
+    pub fn get<'a>(
+        &self,
+        id: Option<PuzzleId>,
+        definitions: &'a Assets<PuzzleDefinition>,
+    ) -> Option<&'a PuzzleDefinition> {
+        id.and_then(|id| self.handles.get(&id))
+            .and_then(|handle| definitions.get(handle))
+    }
+}
+
+#[derive(Default)]
+struct PuzzleDefinitionLoader;
+
+impl AssetLoader for PuzzleDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let definition = ron::de::from_bytes::<PuzzleDefinition>(bytes)?;
+            // failed to load, so the loading screen lists it rather than the round crashing
+            definition.check().map_err(bevy::asset::Error::msg)?;
+            load_context.set_default_asset(LoadedAsset::new(definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle.ron"]
+    }
+}
+
+pub struct PuzzleDefinitionPlugin;
+
+impl Plugin for PuzzleDefinitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PuzzleDefinition>()
+            .init_asset_loader::<PuzzleDefinitionLoader>()
+            .init_resource::<PuzzleDefinitions>()
+            .add_systems(Startup, load_puzzle_definitions);
+    }
+}
+
+// load these right away so they are ready long before the first puzzle starts
+fn load_puzzle_definitions(
+    asset_server: Res<AssetServer>,
+    mut puzzle_definitions: ResMut<PuzzleDefinitions>,
+) {
+    let files = puzzle_definitions.files.clone();
+    for (id, filename) in files {
+        puzzle_definitions
+            .handles
+            .insert(id, asset_server.load(filename));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_tile_for_a_slot_that_isnt_there_is_caught() {
+        let definition: PuzzleDefinition = ron::from_str(
+            r#"(
+                timer_seconds: 20,
+                song: WaltzSong,
+                icon_size: (150.0, 150.0),
+                parameters: (required_placements: 1),
+                slots: [(position: (0.0, 0.0, 0.0))],
+                tiles: [
+                    (filename: "a.png", starting_location: (0.0, 0.0, 0.0), target: Slot(0)),
+                    (filename: "b.png", starting_location: (0.0, 0.0, 0.0), target: Slot(1)),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            definition.check(),
+            Err("b.png is meant for slot 1, but the file has 1 slot(s)".to_owned())
+        );
+    }
+}
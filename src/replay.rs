@@ -0,0 +1,621 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::touch::TouchInput;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::ui::UiSystem;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::config_path;
+use crate::difficulty::Difficulty;
+use crate::picking::Letterbox;
+use crate::players::Players;
+use crate::playlist::Playlist;
+use crate::pointer::{Pointer, PointerSet};
+use crate::puzzle::{PuzzleId, PuzzleRegistry};
+use crate::settings::Settings;
+use crate::GameState;
+use crate::PauseState;
+use crate::PuzzleState;
+use crate::Score;
+use crate::SettingsState;
+
+const LAST_RUN_FILE: &str = "last run.replay.ron";
+
+/// How far the clock moves each frame of a run, while it is recorded and when it is played
+/// back - the same every frame, so the run can't come out differently on a quicker machine
+pub const REPLAY_STEP: Duration = Duration::from_nanos(16_666_667);
+
+/// A whole run, from its first round starting to the final score - what it started with,
+/// then what the player did in each frame
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    puzzles: Vec<String>,
+    // the hot-seat players in turn order - empty for a single team
+    players: Vec<String>,
+    difficulty: Difficulty,
+    // a step of the clock each
+    frames: Vec<Vec<ReplayInput>>,
+}
+
+/// What the puzzles and the buttons are shown of the mouse, the keyboard, touches and
+/// controllers - the pointer rather than whatever was moving it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+enum ReplayInput {
+    // in layout coordinates, so it plays back the same in any size of window
+    PointerMoved(Vec2),
+    PointerPressed,
+    PointerReleased,
+    // already down when the run started, so it isn't a new press
+    PointerHeld,
+    KeyPressed(KeyCode),
+    KeyReleased(KeyCode),
+    // a button being hovered, pressed or let go of - found again by its label
+    Button(String, Interaction),
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|error| error.to_string()))
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::to_string(self).map_err(|error| error.to_string())?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+        }
+        fs::write(path, contents).map_err(|error| error.to_string())
+    }
+}
+
+/// The replay of the last run that was played to the end - kept in the user's config directory
+/// between games, or only in memory if there isn't one (and in the tests)
+#[derive(Resource, Default)]
+pub struct LastRun {
+    pub replay: Option<Replay>,
+    file: Option<PathBuf>,
+}
+
+impl LastRun {
+    pub fn load() -> Self {
+        let file = config_path(LAST_RUN_FILE);
+        // nothing played to the end yet, or a file this version can't read
+        let replay = file.as_deref().and_then(|path| Replay::load(path).ok());
+
+        LastRun { replay, file }
+    }
+
+    fn set(&mut self, replay: Replay) {
+        if let Some(path) = &self.file {
+            match replay.save(path) {
+                Ok(()) => info!("saved a replay of the run to {:?}", path),
+                Err(error) => error!("couldn't save the replay to {:?}: {}", path, error),
+            }
+        }
+        self.replay = Some(replay);
+    }
+}
+
+/// Put in to start watching a replay - from the title screen, or as soon as it is reached
+#[derive(Resource)]
+pub struct WatchReplay(pub Replay);
+
+/// A replay being played back. The player's own input is ignored until it runs out (apart from
+/// esc, which stops it) - it is then kept until the final score screen is left
+#[derive(Resource)]
+pub struct WatchingReplay {
+    frames: Vec<Vec<ReplayInput>>,
+    next_frame: usize,
+    // in layout coordinates - the window's cursor is kept here while it plays
+    pointer_position: Option<Vec2>,
+    // put back when it is over - anything the replay changes on the settings screen is only
+    // for the replay
+    time_update_strategy: Option<TimeUpdateStrategy>,
+    difficulty: Difficulty,
+    settings: Settings,
+}
+
+impl WatchingReplay {
+    fn playing(&self) -> bool {
+        self.next_frame < self.frames.len()
+    }
+
+    fn frame(&self) -> Option<&Vec<ReplayInput>> {
+        self.frames.get(self.next_frame)
+    }
+}
+
+pub fn playing_replay(watching: Option<Res<WatchingReplay>>) -> bool {
+    watching.is_some_and(|watching| watching.playing())
+}
+
+#[derive(Resource, Default)]
+struct ReplayRecorder {
+    // None between runs
+    recording: Option<Replay>,
+    // this frame's input so far - it is added to the recording at the end of the frame
+    inputs: Vec<ReplayInput>,
+    // where the pointer was last written down
+    pointer_position: Option<Vec2>,
+    // put back when the run is over
+    time_update_strategy: Option<TimeUpdateStrategy>,
+}
+
+pub struct ReplayPlugin;
+
+/// Every run played to the end is recorded, and can be watched back from the title screen (or
+/// with --replay <file>). The clock goes by REPLAY_STEP a frame for the whole of a run, so
+/// played back with the same input it comes out the same - the timer, the mistakes and the
+/// score. Watching one doesn't save anything
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastRun>()
+            .init_resource::<ReplayRecorder>()
+            .add_systems(
+                PreUpdate,
+                (
+                    ignore_player_input
+                        .before(InputSystem)
+                        .run_if(playing_replay),
+                    replay_pointer_and_keys
+                        .after(InputSystem)
+                        .before(PointerSet)
+                        .before(UiSystem::Focus)
+                        .run_if(playing_replay),
+                    // after the ui has worked out what the replayed pointer is over
+                    replay_buttons
+                        .after(replay_pointer_and_keys)
+                        .after(UiSystem::Focus)
+                        .run_if(playing_replay),
+                    record_input
+                        .after(PointerSet)
+                        .after(UiSystem::Focus)
+                        .run_if(not(resource_exists::<WatchingReplay>())),
+                ),
+            )
+            .add_systems(Last, finish_recorded_frame)
+            .add_systems(
+                Update,
+                start_watching_replay
+                    .run_if(in_state(GameState::TitleScreen))
+                    .run_if(resource_exists::<WatchReplay>()),
+            )
+            .add_systems(OnEnter(GameState::Puzzle), start_recording)
+            .add_systems(OnEnter(GameState::ShowScore), save_recording)
+            .add_systems(OnExit(GameState::ShowScore), stop_watching_replay)
+            .add_systems(
+                OnEnter(GameState::TitleScreen),
+                (discard_recording, stop_watching_replay),
+            );
+    }
+}
+
+fn start_watching_replay(
+    mut commands: Commands,
+    watch_replay: Res<WatchReplay>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut playlist: ResMut<Playlist>,
+    mut players: ResMut<Players>,
+    mut score: ResMut<Score>,
+    mut difficulty: ResMut<Difficulty>,
+    settings: Res<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    commands.remove_resource::<WatchReplay>();
+    let replay = &watch_replay.0;
+
+    let puzzles: Option<Vec<PuzzleId>> = replay
+        .puzzles
+        .iter()
+        .map(|name| {
+            puzzle_registry
+                .iter()
+                .map(|(id, _)| *id)
+                .find(|id| id.0 == name)
+        })
+        .collect();
+    let Some(puzzles) = puzzles else {
+        error!(
+            "the replay has rounds this version doesn't: {:?}",
+            replay.puzzles
+        );
+        return;
+    };
+
+    *playlist = Playlist::new("replay", puzzles);
+    playlist.start();
+    *players = Players::named(&replay.players);
+    *score = Score::default();
+
+    commands.insert_resource(WatchingReplay {
+        frames: replay.frames.clone(),
+        next_frame: 0,
+        pointer_position: None,
+        time_update_strategy: Some(std::mem::replace(
+            &mut *time_update_strategy,
+            TimeUpdateStrategy::ManualDuration(REPLAY_STEP),
+        )),
+        difficulty: std::mem::replace(&mut *difficulty, replay.difficulty),
+        settings: settings.clone(),
+    });
+    next_state.set(GameState::Puzzle);
+}
+
+// so that only the replay is moving the pointer and pressing keys - esc stops it
+fn ignore_player_input(
+    mut keyboard_events: ResMut<Events<KeyboardInput>>,
+    mut mouse_button_events: ResMut<Events<MouseButtonInput>>,
+    mut cursor_moved_events: ResMut<Events<CursorMoved>>,
+    mut touch_events: ResMut<Events<TouchInput>>,
+    mut watching: ResMut<WatchingReplay>,
+    mut score: ResMut<Score>,
+    mut players: ResMut<Players>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    let stopped = keyboard_events
+        .drain()
+        .any(|event| event.key_code == Some(KeyCode::Escape));
+    mouse_button_events.clear();
+    cursor_moved_events.clear();
+    touch_events.clear();
+
+    if stopped {
+        // the rest is skipped - the same as quitting from the pause menu
+        watching.next_frame = watching.frames.len();
+        *score = Score::default();
+        players.reset_scores();
+
+        next_settings_state.set(SettingsState::Closed);
+        next_pause_state.set(PauseState::Running);
+        next_puzzle_state.set(PuzzleState::GetReady);
+        next_game_state.set(GameState::TitleScreen);
+    }
+}
+
+fn replay_pointer_and_keys(
+    mut watching: ResMut<WatchingReplay>,
+    letterbox: Res<Letterbox>,
+    mut windows: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut mouse_button_input: ResMut<Input<MouseButton>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut cursor_moved_events: EventWriter<CursorMoved>,
+) {
+    let Some(frame) = watching.frame() else {
+        return;
+    };
+    let Ok((window_entity, mut window)) = windows.get_single_mut() else {
+        return;
+    };
+
+    // whatever was held down to start it (the button that was clicked, for one) is let go
+    if watching.next_frame == 0 {
+        mouse_button_input.reset_all();
+        keys.reset_all();
+    }
+
+    let mut pointer_position = watching.pointer_position;
+    for input in frame.iter() {
+        match input {
+            ReplayInput::PointerMoved(position) => {
+                pointer_position = Some(*position);
+                cursor_moved_events.send(CursorMoved {
+                    window: window_entity,
+                    position: letterbox.layout_to_window(*position),
+                });
+            }
+            ReplayInput::PointerPressed => mouse_button_input.press(MouseButton::Left),
+            ReplayInput::PointerReleased => mouse_button_input.release(MouseButton::Left),
+            ReplayInput::PointerHeld => {
+                mouse_button_input.press(MouseButton::Left);
+                mouse_button_input.clear_just_pressed(MouseButton::Left);
+            }
+            ReplayInput::KeyPressed(key) => keys.press(*key),
+            ReplayInput::KeyReleased(key) => keys.release(*key),
+            ReplayInput::Button(..) => {}
+        }
+    }
+    watching.pointer_position = pointer_position;
+
+    // the ui goes by the window's cursor - which the player's mouse could have moved since
+    let cursor = pointer_position.map(|position| letterbox.layout_to_window(position));
+    if window.cursor_position() != cursor {
+        window.set_cursor_position(cursor);
+    }
+}
+
+// the first line of text on a button
+fn button_label<'a>(children: &Children, texts: &'a Query<&Text>) -> Option<&'a str> {
+    children
+        .iter()
+        .find_map(|child| texts.get(*child).ok())
+        .and_then(|text| text.sections.first())
+        .map(|section| section.value.as_str())
+}
+
+fn replay_buttons(
+    mut watching: ResMut<WatchingReplay>,
+    mut buttons: Query<(Entity, &mut Interaction, &Children, &Visibility), With<Button>>,
+    texts: Query<&Text>,
+) {
+    if let Some(frame) = watching.frame() {
+        for input in frame.iter() {
+            let ReplayInput::Button(label, interaction) = input else {
+                continue;
+            };
+
+            // one that is showing if there are two the same - but a button that has just been
+            // hidden is still let go of
+            let button = buttons
+                .iter()
+                .filter(|(_, _, children, _)| {
+                    button_label(children, &texts) == Some(label.as_str())
+                })
+                .max_by_key(|(_, _, _, visibility)| **visibility != Visibility::Hidden)
+                .map(|(entity, ..)| entity);
+            match button.and_then(|button| buttons.get_mut(button).ok()) {
+                // usually already done by the ui, from where the pointer is
+                Some((_, mut button_interaction, _, _)) => {
+                    button_interaction.set_if_neq(*interaction);
+                }
+                None => warn!("the replay used a {:?} button that isn't there", label),
+            }
+        }
+    }
+
+    watching.next_frame += 1;
+}
+
+fn record_input(
+    mut recorder: ResMut<ReplayRecorder>,
+    pointer: Res<Pointer>,
+    letterbox: Res<Letterbox>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Query<(Ref<Interaction>, &Children), With<Button>>,
+    texts: Query<&Text>,
+) {
+    let position = pointer
+        .position()
+        .map(|position| letterbox.window_to_layout(position));
+    if position != recorder.pointer_position {
+        if let Some(position) = position {
+            recorder.inputs.push(ReplayInput::PointerMoved(position));
+        }
+        recorder.pointer_position = position;
+    }
+
+    if pointer.just_pressed() {
+        recorder.inputs.push(ReplayInput::PointerPressed);
+    }
+    if pointer.just_released() {
+        recorder.inputs.push(ReplayInput::PointerReleased);
+    }
+
+    for key in keys.get_just_pressed() {
+        recorder.inputs.push(ReplayInput::KeyPressed(*key));
+    }
+    for key in keys.get_just_released() {
+        recorder.inputs.push(ReplayInput::KeyReleased(*key));
+    }
+
+    // new buttons start out with nothing to tell - unless they were pressed straight away
+    for (interaction, children) in buttons.iter() {
+        if !interaction.is_changed()
+            || (interaction.is_added() && *interaction == Interaction::None)
+        {
+            continue;
+        }
+        if let Some(label) = button_label(children, &texts) {
+            recorder
+                .inputs
+                .push(ReplayInput::Button(label.to_owned(), *interaction));
+        }
+    }
+}
+
+// a run starts with its first round - after that, rounds starting are part of it
+fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    pointer: Res<Pointer>,
+    letterbox: Res<Letterbox>,
+    playlist: Res<Playlist>,
+    players: Res<Players>,
+    difficulty: Res<Difficulty>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    watching: Option<Res<WatchingReplay>>,
+) {
+    if recorder.recording.is_some() || watching.is_some() {
+        return;
+    }
+
+    // from the next frame on - this one is still getting the round ready, with nothing counting
+    // the time yet
+    recorder.time_update_strategy = Some(std::mem::replace(
+        &mut *time_update_strategy,
+        TimeUpdateStrategy::ManualDuration(REPLAY_STEP),
+    ));
+
+    recorder.recording = Some(Replay {
+        puzzles: playlist
+            .puzzles()
+            .iter()
+            .map(|id| id.0.to_owned())
+            .collect(),
+        players: players.names(),
+        difficulty: *difficulty,
+        frames: Vec::new(),
+    });
+
+    // the buttons of the screen that started it have gone, and the pointer is wherever it was
+    // left - possibly still held down from the click
+    recorder.inputs.retain(|input| {
+        !matches!(
+            input,
+            ReplayInput::Button(..) | ReplayInput::PointerMoved(_)
+        )
+    });
+    if let Some(position) = pointer.position() {
+        let position = letterbox.window_to_layout(position);
+        recorder
+            .inputs
+            .insert(0, ReplayInput::PointerMoved(position));
+        recorder.pointer_position = Some(position);
+    }
+    if pointer.pressed() && !pointer.just_pressed() {
+        recorder.inputs.push(ReplayInput::PointerHeld);
+    }
+}
+
+fn finish_recorded_frame(mut recorder: ResMut<ReplayRecorder>) {
+    let inputs = std::mem::take(&mut recorder.inputs);
+
+    if let Some(recording) = recorder.recording.as_mut() {
+        recording.frames.push(inputs);
+    }
+}
+
+fn save_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut last_run: ResMut<LastRun>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if let Some(strategy) = recorder.time_update_strategy.take() {
+        *time_update_strategy = strategy;
+    }
+    if let Some(recording) = recorder.recording.take() {
+        last_run.set(recording);
+    }
+}
+
+// quit part way through
+fn discard_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+) {
+    if let Some(strategy) = recorder.time_update_strategy.take() {
+        *time_update_strategy = strategy;
+    }
+    recorder.recording = None;
+}
+
+fn stop_watching_replay(
+    mut commands: Commands,
+    watching: Option<ResMut<WatchingReplay>>,
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some(mut watching) = watching else {
+        return;
+    };
+
+    if let Some(strategy) = watching.time_update_strategy.take() {
+        *time_update_strategy = strategy;
+    }
+    *difficulty = watching.difficulty;
+    settings.set_if_neq(watching.settings.clone());
+    commands.remove_resource::<WatchingReplay>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drag_and_drop::SpriteData;
+    use crate::puzzle_definition::{TileTarget, REINDEER};
+    use crate::testing::{TestGame, FRAME};
+    use crate::ScoreReason;
+
+    // what the score screen is worked out from, with when each change happened
+    fn score_history(game: &TestGame) -> Vec<(ScoreReason, f32, Duration)> {
+        game.world()
+            .resource::<Score>()
+            .history
+            .iter()
+            .map(|change| (change.reason, change.delta_score, change.timestamp))
+            .collect()
+    }
+
+    // one tile dropped on another tile's answer
+    fn wrong_drop(game: &mut TestGame) -> (Vec2, Vec2) {
+        let mut tiles = game.world_mut().query::<&SpriteData>();
+        let tiles: Vec<&SpriteData> = tiles
+            .iter(game.world())
+            .filter(|tile| matches!(tile.target, TileTarget::Slot(_)))
+            .collect();
+
+        tiles
+            .iter()
+            .find_map(|tile| {
+                tiles
+                    .iter()
+                    .map(|other| other.correct_location)
+                    .find(|drop| drop.distance(tile.correct_location) > 200.0)
+                    .map(|drop| (tile.starting_location.truncate(), drop.truncate()))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn a_recorded_run_plays_back_the_same() {
+        let mut game = TestGame::new();
+        game.play_round(REINDEER);
+
+        let (from, to) = wrong_drop(&mut game);
+        game.drag(from, to);
+        game.run_for(Duration::from_secs(1));
+        for (from, to) in game.solution() {
+            game.drag(from, to);
+        }
+        game.run_for(Duration::from_secs(1));
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        game.click_button("show final score");
+        // by way of the round being cleaned up
+        game.update();
+        assert_eq!(game.game_state(), GameState::ShowScore);
+
+        let recorded = score_history(&game);
+        assert!(matches!(
+            recorded[..],
+            [(ScoreReason::WrongTile, penalty, _), (ScoreReason::TimeBonus, bonus, timestamp)]
+                if penalty < 0.0 && bonus > 0.0 && timestamp > Duration::from_secs(1)
+        ));
+        let replay = game
+            .world()
+            .resource::<LastRun>()
+            .replay
+            .clone()
+            .expect("the run wasn't recorded");
+
+        // by way of a file
+        let replay: Replay = ron::from_str(&ron::to_string(&replay).unwrap()).unwrap();
+        let frames = replay.frames.len();
+
+        // a machine that updates less often doesn't watch it any differently
+        let mut watched = TestGame::new();
+        watched
+            .world_mut()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME * 3));
+        watched.world_mut().insert_resource(WatchReplay(replay));
+        for _ in 0..frames + 10 {
+            if watched.game_state() == GameState::ShowScore {
+                break;
+            }
+            watched.update();
+        }
+
+        assert_eq!(watched.game_state(), GameState::ShowScore);
+        assert_eq!(score_history(&watched), recorded);
+        // watching it isn't a run of its own
+        assert!(watched.world().resource::<LastRun>().replay.is_none());
+    }
+}
@@ -1,53 +1,56 @@
+use crate::palette::Palette;
+use crate::players::Players;
+use crate::puzzle::PuzzleId;
+use crate::tween::Easing;
 use crate::GameState;
+use crate::PauseState;
 use crate::Score;
+use crate::ScoreChange;
+use crate::ScorePopup;
+use crate::ScoreReason;
 use bevy::prelude::*;
+use std::time::Duration;
 
 pub struct ScorePlugin;
 
 const SCORE_FONT_SIZE: f32 = 80.;
-const SCORE_CHANGE_SPEED: f32 = 50.0;
+// how long each change takes to count up on screen
+const SCORE_CHANGE_SECONDS: f32 = 0.8;
+
+const POPUP_FONT_SIZE: f32 = 50.;
+const POPUP_SECONDS: f32 = 1.2;
+const POPUP_RISE: f32 = 80.;
+
+// time bonuses float up from just under the timer bar
+pub const TIME_BONUS_POSITION: Vec2 = Vec2::new(0., 250.);
 
 #[derive(Component)]
 struct ScoreText;
 
+#[derive(Component)]
+struct ScorePopupText {
+    start: Vec2,
+    elapsed: f32,
+}
+
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::PuzzleTurkeyMistletoe), setup_score) // call only once
-            //.add_systems(OnEnter(GameState::PuzzlePeriodicTable), unhide_score) // call only once
-            .add_systems(OnEnter(GameState::PuzzleCranberries), unhide_score) // call only once - @rch: temporary
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleTurkeyMistletoe)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzlePeriodicTable)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleTwelveDays)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleReindeer)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleWaltz)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleGrocery)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleFamilyTree)),
-            )
-            .add_systems(
-                Update,
-                update_score.run_if(in_state(GameState::PuzzleCranberries)),
-            )
-            .add_systems(OnExit(GameState::PuzzleCranberries), hide_score);
+        // the score is put up by whichever round is played first - whether that came from
+        // the instructions or from stage select
+        app.add_systems(
+            OnEnter(GameState::Puzzle),
+            setup_score.run_if(not(any_with_component::<ScoreText>())),
+        )
+        .add_systems(
+            Update,
+            (update_score, spawn_score_popups, update_score_popups)
+                .run_if(in_state(GameState::Puzzle))
+                .run_if(in_state(PauseState::Running)),
+        )
+        .add_systems(OnExit(GameState::Puzzle), cleanup_score_popups)
+        .add_systems(OnEnter(GameState::ShowScore), cleanup_score)
+        // quitting a game from the pause menu
+        .add_systems(OnEnter(GameState::TitleScreen), cleanup_score);
     }
 }
 
@@ -72,52 +75,229 @@ fn setup_score(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
-fn update_score(mut score: ResMut<Score>, mut score_text_query: Query<&mut Text, With<ScoreText>>) {
-    // update the text
-    for mut score_text in &mut score_text_query {
-        score_text.sections[0].value = format!("current score: {:}", score.current_score as i32);
-        if !score.score_change_vector.is_empty() {
-            match score.score_change_vector.first().unwrap().delta_score > 0.0 {
-                true => score_text.sections[0].style.color = Color::GREEN,
-                false => score_text.sections[0].style.color = Color::RED,
-            }
-        } else {
-            score_text.sections[0].style.color = Color::WHITE;
+/// How a round went - made up from the run history
+#[derive(Debug, Clone, Copy)]
+pub struct RoundSummary {
+    pub puzzle: PuzzleId,
+    // how long it took to solve - None if the time ran out
+    pub solved_in: Option<Duration>,
+    pub time_bonus: f32,
+    pub mistakes: u32,
+    pub net_score: f32,
+}
+
+impl RoundSummary {
+    fn new(puzzle: PuzzleId) -> Self {
+        RoundSummary {
+            puzzle,
+            solved_in: None,
+            time_bonus: 0.0,
+            mistakes: 0,
+            net_score: 0.0,
         }
     }
+}
+
+impl Score {
+    /// Queue a change to be counted up (or down) on screen, and record it in the run history.
+    /// `position` is where in the world it happened, for the popup.
+    pub fn change(
+        &mut self,
+        delta_score: f32,
+        puzzle: PuzzleId,
+        reason: ScoreReason,
+        timestamp: Duration,
+        position: Vec2,
+    ) {
+        let change = ScoreChange {
+            delta_score,
+            initial_score: self.current_score,
+            puzzle,
+            reason,
+            timestamp,
+        };
+        self.history.push(change.clone());
+        self.score_change_queue.push_back(change);
+        self.popups.push(ScorePopup {
+            delta_score,
+            position,
+        });
+    }
 
-    // see if there is a score change to process
-    if !score.score_change_vector.is_empty() {
-        let delta_score = score.score_change_vector.first().unwrap().delta_score;
-        let initial_score = score.score_change_vector.first().unwrap().initial_score;
+    /// The time bonus, mistakes and net score of each round - rounds that ran out of time
+    /// without a mistake get an empty summary
+    pub fn round_summaries(&self, puzzles: &[PuzzleId]) -> Vec<RoundSummary> {
+        let mut summaries: Vec<RoundSummary> = puzzles
+            .iter()
+            .map(|puzzle| RoundSummary::new(*puzzle))
+            .collect();
 
-        score.current_score += delta_score / SCORE_CHANGE_SPEED;
+        for change in self.history.iter() {
+            let index = match summaries
+                .iter()
+                .position(|summary| summary.puzzle == change.puzzle)
+            {
+                Some(index) => index,
+                None => {
+                    summaries.push(RoundSummary::new(change.puzzle));
+                    summaries.len() - 1
+                }
+            };
 
-        // see if we are done changing the score
-        if ((delta_score + initial_score) - score.current_score).abs() < 0.5 {
-            // pop off this score change
-            score.score_change_vector.pop();
-            // add the mistake
-            if delta_score < 0.0 {
-                score.mistakes = score.mistakes + 1;
+            let summary = &mut summaries[index];
+            match change.reason {
+                ScoreReason::TimeBonus => {
+                    summary.solved_in = Some(change.timestamp);
+                    summary.time_bonus += change.delta_score;
+                }
+                ScoreReason::WrongTile | ScoreReason::WrongElement | ScoreReason::WrongClick => {
+                    summary.mistakes += 1
+                }
             }
+            summary.net_score += change.delta_score;
+        }
+
+        summaries
+    }
 
-            // if there is a new one - update the next elements current score from what was originally entered
-            if !score.score_change_vector.is_empty() {
-                score.score_change_vector.first_mut().unwrap().initial_score = score.current_score;
+    /// Finish off any score changes that are still being counted up (or down)
+    pub fn settle(&mut self) {
+        if let Some(first) = self.score_change_queue.front() {
+            self.current_score = first.initial_score;
+        }
+
+        for ScoreChange { delta_score, .. } in self.score_change_queue.iter() {
+            self.current_score += delta_score;
+            if *delta_score < 0.0 {
+                self.mistakes += 1;
             }
         }
+
+        self.clear_pending();
+    }
+
+    /// Drop anything still waiting to be shown - the changes themselves are left in the history
+    pub fn clear_pending(&mut self) {
+        self.score_change_queue.clear();
+        self.score_change_elapsed = 0.0;
+        self.popups.clear();
     }
 }
 
-fn unhide_score(mut score_text_query: Query<&mut Text, With<ScoreText>>) {
+fn update_score(
+    mut score: ResMut<Score>,
+    players: Res<Players>,
+    time: Res<Time>,
+    palette: Res<Palette>,
+    mut score_text_query: Query<&mut Text, With<ScoreText>>,
+) {
+    // update the text
     for mut score_text in &mut score_text_query {
-        score_text.sections[0].style.font_size = SCORE_FONT_SIZE;
+        let label = match players.current_name() {
+            // hot-seat - show whose turn it is
+            Some(name) => format!("{:}'s score: {:}", name, score.current_score as i32),
+            None => format!("current score: {:}", score.current_score as i32),
+        };
+        // the change being counted is spelled out with its sign, not just shown by the color
+        score_text.sections[0].value = match score.score_change_queue.front() {
+            Some(change) => format!("{:} ({:+})", label, change.delta_score as i32),
+            None => label,
+        };
+        score_text.sections[0].style.color = match score.score_change_queue.front() {
+            Some(change) if change.delta_score > 0.0 => palette.good(),
+            Some(_) => palette.bad(),
+            None => Color::WHITE,
+        };
+    }
+
+    // count up (or down) the oldest change
+    let Some(change) = score.score_change_queue.front() else {
+        return;
+    };
+    let (delta_score, initial_score) = (change.delta_score, change.initial_score);
+
+    score.score_change_elapsed += time.delta_seconds();
+    let t = (score.score_change_elapsed / SCORE_CHANGE_SECONDS).min(1.0);
+    score.current_score = initial_score + delta_score * Easing::OutCubic.ease(t);
+
+    if t >= 1.0 {
+        score.score_change_queue.pop_front();
+        score.score_change_elapsed = 0.0;
+        if delta_score < 0.0 {
+            score.mistakes += 1;
+        }
+
+        // the next change carries on from wherever this one finished
+        let current_score = score.current_score;
+        if let Some(next) = score.score_change_queue.front_mut() {
+            next.initial_score = current_score;
+        }
     }
 }
 
-fn hide_score(mut score_text_query: Query<&mut Text, With<ScoreText>>) {
-    for mut score_text in &mut score_text_query {
-        score_text.sections[0].style.font_size = 0.; // can't seem to remove visibility of a text bundle so set font to 0
+fn spawn_score_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    palette: Res<Palette>,
+    mut score: ResMut<Score>,
+) {
+    for popup in score.popups.drain(..) {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("{:+}", popup.delta_score as i32),
+                    TextStyle {
+                        font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                        font_size: POPUP_FONT_SIZE,
+                        color: if popup.delta_score > 0.0 {
+                            palette.good()
+                        } else {
+                            palette.bad()
+                        },
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                // above the tiles and whatever is being dragged
+                transform: Transform::from_translation(popup.position.extend(50.0)),
+                ..default()
+            },
+            ScorePopupText {
+                start: popup.position,
+                elapsed: 0.0,
+            },
+        ));
+    }
+}
+
+// the popups drift upwards and fade away
+fn update_score_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popup_query: Query<(Entity, &mut ScorePopupText, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popup_query.iter_mut() {
+        popup.elapsed += time.delta_seconds();
+        let t = (popup.elapsed / POPUP_SECONDS).min(1.0);
+
+        transform.translation.y = popup.start.y + POPUP_RISE * Easing::OutCubic.ease(t);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1.0 - t);
+        }
+
+        if t >= 1.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn cleanup_score_popups(mut commands: Commands, popup_query: Query<Entity, With<ScorePopupText>>) {
+    for entity in popup_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn cleanup_score(mut commands: Commands, score_text_query: Query<Entity, With<ScoreText>>) {
+    for score_text in score_text_query.iter() {
+        commands.entity(score_text).despawn_recursive();
     }
 }
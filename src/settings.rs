@@ -0,0 +1,457 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::audio::AudioBus;
+use crate::config::config_path;
+use crate::palette::{ColorblindMode, Palette};
+use crate::pause::PauseToggle;
+use crate::picking::{LayoutRoot, LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::replay::WatchingReplay;
+use crate::ButtonColors;
+use crate::SettingsState;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+// how far the - and + buttons move a volume
+const VOLUME_STEP: f32 = 0.1;
+
+/// The player's settings - kept in the user's config directory and applied at startup
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    // from 0 to 1
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    // the snow stays where it is instead of falling
+    pub reduced_motion: bool,
+    pub colorblind_mode: ColorblindMode,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            fullscreen: false,
+            vsync: true,
+            reduced_motion: false,
+            colorblind_mode: ColorblindMode::Off,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let Some(path) = config_path(SETTINGS_FILE) else {
+            return Settings::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|error| {
+                warn!("ignoring unreadable settings in {:?}: {}", path, error);
+                Settings::default()
+            }),
+            // nothing saved yet
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub fn save(&self) {
+        let Some(path) = config_path(SETTINGS_FILE) else {
+            warn!("no config directory - the settings can't be saved");
+            return;
+        };
+
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|error| error.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|error| error.to_string())
+            });
+
+        if let Err(error) = result {
+            error!("couldn't save the settings to {:?}: {}", path, error);
+        }
+    }
+
+    /// How loud the sounds on a bus play, master volume included
+    pub fn volume(&self, bus: AudioBus) -> f32 {
+        self.master_volume
+            * match bus {
+                AudioBus::Music => self.music_volume,
+                AudioBus::Effects => self.effects_volume,
+            }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    fn volume_mut(&mut self, volume: VolumeSetting) -> &mut f32 {
+        match volume {
+            VolumeSetting::Master => &mut self.master_volume,
+            VolumeSetting::Music => &mut self.music_volume,
+            VolumeSetting::Effects => &mut self.effects_volume,
+        }
+    }
+
+    fn label(&self, setting: Setting) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+
+        match setting {
+            Setting::Volume(volume) => {
+                let name = match volume {
+                    VolumeSetting::Master => "master volume",
+                    VolumeSetting::Music => "music volume",
+                    VolumeSetting::Effects => "effects volume",
+                };
+                let level = match volume {
+                    VolumeSetting::Master => self.master_volume,
+                    VolumeSetting::Music => self.music_volume,
+                    VolumeSetting::Effects => self.effects_volume,
+                };
+                format!("{}: {}%", name, (level * 100.0).round())
+            }
+            Setting::Fullscreen => format!("fullscreen: {}", on_off(self.fullscreen)),
+            Setting::Vsync => format!("vsync: {}", on_off(self.vsync)),
+            Setting::ReducedMotion => format!("reduced motion: {}", on_off(self.reduced_motion)),
+            Setting::Colors => format!("colors: {}", self.colorblind_mode.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VolumeSetting {
+    Master,
+    Music,
+    Effects,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Setting {
+    Volume(VolumeSetting),
+    Fullscreen,
+    Vsync,
+    ReducedMotion,
+    Colors,
+}
+
+#[derive(Component)]
+struct SettingsMenu;
+
+// the text showing a setting's current value
+#[derive(Component)]
+struct SettingLabel(Setting);
+
+#[derive(Component)]
+enum SettingsButton {
+    Quieter(VolumeSetting),
+    Louder(VolumeSetting),
+    Toggle(Setting),
+    Back,
+}
+
+// hidden while the settings are open, so only the settings can be clicked (or reached with the d-pad)
+#[derive(Component)]
+struct CoveredBySettings;
+
+pub struct SettingsPlugin;
+
+/// The settings screen opens over the title screen or the pause menu, and the settings are
+/// saved when it is closed (but not from a replay, which puts them back when it is over). The
+/// window and the palette keep up with the settings as they change
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SettingsState::Open), setup_settings_menu)
+            .add_systems(
+                Update,
+                (
+                    // esc closes the settings rather than the pause menu underneath
+                    close_settings_with_esc.before(PauseToggle),
+                    click_settings_button,
+                    update_setting_labels,
+                )
+                    .chain()
+                    .run_if(in_state(SettingsState::Open)),
+            )
+            .add_systems(OnExit(SettingsState::Open), cleanup_settings_menu)
+            .add_systems(Update, (apply_display_settings, apply_colorblind_mode));
+    }
+}
+
+fn setup_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    settings: Res<Settings>,
+    layout_roots: Query<&Children, With<LayoutRoot>>,
+    screens: Query<&Visibility, With<Node>>,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    // whatever screens are up
+    for entity in layout_roots
+        .iter()
+        .flat_map(|children| children.iter().copied())
+    {
+        if screens
+            .get(entity)
+            .is_ok_and(|visibility| *visibility != Visibility::Hidden)
+        {
+            commands
+                .entity(entity)
+                .insert((Visibility::Hidden, CoveredBySettings));
+        }
+    }
+
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+        .with_text_alignment(TextAlignment::Center)
+    };
+    let button = |width: f32| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(60.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: button_colors.normal.into(),
+        ..default()
+    };
+    let row = || NodeBundle {
+        style: Style {
+            margin: UiRect::top(Val::Px(15.0)),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+                focus_policy: FocusPolicy::Block,
+                // over the pause menu
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            SettingsMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("settings".to_string(), 100.0));
+
+            for volume in [
+                VolumeSetting::Master,
+                VolumeSetting::Music,
+                VolumeSetting::Effects,
+            ] {
+                parent.spawn(row()).with_children(|parent| {
+                    parent
+                        .spawn((button(60.0), SettingsButton::Quieter(volume)))
+                        .with_children(|parent| {
+                            parent.spawn(text("-".to_string(), 50.0));
+                        });
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(420.0),
+                                justify_content: JustifyContent::Center,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            let setting = Setting::Volume(volume);
+                            parent.spawn((
+                                text(settings.label(setting), 50.0),
+                                SettingLabel(setting),
+                            ));
+                        });
+                    parent
+                        .spawn((button(60.0), SettingsButton::Louder(volume)))
+                        .with_children(|parent| {
+                            parent.spawn(text("+".to_string(), 50.0));
+                        });
+                });
+            }
+
+            for setting in [
+                Setting::Fullscreen,
+                Setting::Vsync,
+                Setting::ReducedMotion,
+                Setting::Colors,
+            ] {
+                parent.spawn(row()).with_children(|parent| {
+                    parent
+                        .spawn((button(540.0), SettingsButton::Toggle(setting)))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                text(settings.label(setting), 50.0),
+                                SettingLabel(setting),
+                            ));
+                        });
+                });
+            }
+
+            parent.spawn(row()).with_children(|parent| {
+                parent
+                    .spawn((button(300.0), SettingsButton::Back))
+                    .with_children(|parent| {
+                        parent.spawn(text("back".to_string(), 50.0));
+                    });
+            });
+        });
+}
+
+fn close_settings_with_esc(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        keys.clear_just_pressed(KeyCode::Escape);
+        next_settings_state.set(SettingsState::Closed);
+    }
+}
+
+fn click_settings_button(
+    button_colors: Res<ButtonColors>,
+    mut settings: ResMut<Settings>,
+    mut next_settings_state: ResMut<NextState<SettingsState>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &SettingsButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => match *button {
+                SettingsButton::Quieter(volume) => {
+                    let level = settings.volume_mut(volume);
+                    // rounded so the steps don't drift away from whole percentages
+                    *level = ((*level - VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+                    *level = level.clamp(0.0, 1.0);
+                }
+                SettingsButton::Louder(volume) => {
+                    let level = settings.volume_mut(volume);
+                    *level = ((*level + VOLUME_STEP) / VOLUME_STEP).round() * VOLUME_STEP;
+                    *level = level.clamp(0.0, 1.0);
+                }
+                SettingsButton::Toggle(Setting::Fullscreen) => {
+                    settings.fullscreen = !settings.fullscreen;
+                }
+                SettingsButton::Toggle(Setting::Vsync) => settings.vsync = !settings.vsync,
+                SettingsButton::Toggle(Setting::ReducedMotion) => {
+                    settings.reduced_motion = !settings.reduced_motion;
+                }
+                SettingsButton::Toggle(Setting::Colors) => {
+                    settings.colorblind_mode = settings.colorblind_mode.next();
+                }
+                SettingsButton::Toggle(Setting::Volume(_)) => {}
+                SettingsButton::Back => next_settings_state.set(SettingsState::Closed),
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn update_setting_labels(settings: Res<Settings>, mut labels: Query<(&SettingLabel, &mut Text)>) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (label, mut text) in labels.iter_mut() {
+        text.sections[0].value = settings.label(label.0);
+    }
+}
+
+fn cleanup_settings_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    settings_menu_query: Query<Entity, With<SettingsMenu>>,
+    covered_query: Query<Entity, With<CoveredBySettings>>,
+    watching_replay: Option<Res<WatchingReplay>>,
+) {
+    for entity in settings_menu_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in covered_query.iter() {
+        commands
+            .entity(entity)
+            .insert(Visibility::Inherited)
+            .remove::<CoveredBySettings>();
+    }
+
+    if watching_replay.is_none() {
+        settings.save();
+    }
+}
+
+fn apply_display_settings(
+    settings: Res<Settings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let Ok(mut window) = windows.get_single_mut() else {
+        return;
+    };
+
+    // only touched when they differ, so the window only hears about real changes
+    if window.mode != settings.window_mode() {
+        window.mode = settings.window_mode();
+    }
+    if window.present_mode != settings.present_mode() {
+        window.present_mode = settings.present_mode();
+    }
+}
+
+fn apply_colorblind_mode(settings: Res<Settings>, mut palette: ResMut<Palette>) {
+    if settings.is_changed() && palette.mode != settings.colorblind_mode {
+        palette.mode = settings.colorblind_mode;
+    }
+}
@@ -1,5 +1,15 @@
+use crate::difficulty::Difficulty;
+use crate::high_scores::{HighScore, HighScores, MAX_NAME_LENGTH};
+use crate::palette::Palette;
+use crate::picking::LAYOUT_WIDTH;
+use crate::players::Players;
+use crate::playlist::Playlist;
+use crate::puzzle::PuzzleRegistry;
+use crate::replay::WatchingReplay;
 use crate::BackgroundImage;
+use crate::ButtonColors;
 use crate::GameState;
+use crate::PuzzleState;
 use crate::Score;
 use crate::SoundEvent;
 use crate::SoundsEnum;
@@ -8,31 +18,85 @@ use bevy::prelude::*;
 const FINAL_SCORE_FONT_SIZE: f32 = 120.;
 pub struct ShowScorePlugin;
 
+#[derive(Component)]
+struct ShowScoreScreen;
+
+#[derive(Component)]
+enum ShowScoreButton {
+    PlayAgain,
+    BackToTitle,
+    SaveHighScore,
+}
+
+#[derive(Component)]
+struct HighScoreText;
+
+/// The name being typed in for a run that made the high score table
+#[derive(Resource)]
+struct HighScoreEntry {
+    name: String,
+    mode: String,
+}
+
 impl Plugin for ShowScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::ShowScore), setup_show_score);
+        app.add_systems(OnEnter(GameState::ShowScore), setup_show_score)
+            .add_systems(
+                Update,
+                (
+                    click_show_score_button,
+                    type_high_score_name,
+                    update_high_score_text,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::ShowScore)),
+            )
+            .add_systems(OnExit(GameState::ShowScore), cleanup_show_score);
     }
 }
 
 fn setup_show_score(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
     mut sound_event_writer: EventWriter<SoundEvent>,
-    score: Res<Score>,
+    mut score: ResMut<Score>,
+    players: Res<Players>,
+    playlist: Res<Playlist>,
+    difficulty: Res<Difficulty>,
+    mut high_scores: ResMut<HighScores>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    palette: Res<Palette>,
+    watching_replay: Option<Res<WatchingReplay>>,
 ) {
-    commands.spawn((SpriteBundle {
-        texture: asset_server.load("snowglobe/text/show score.png"),
-        sprite: Sprite {
-            custom_size: Some(Vec2 { x: 1600., y: 800. }),
-            ..default()
-        },
-        transform: Transform {
-            translation: Vec3::new(0.0, 0.0, 0.0),
+    // don't leave the last time bonus half counted
+    score.settle();
+
+    let mode = match players.is_hot_seat() {
+        true => format!(
+            "hot-seat {} ({})",
+            playlist.name(),
+            difficulty.preset().name()
+        ),
+        false => format!("{} ({})", playlist.name(), difficulty.preset().name()),
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("snowglobe/text/show score.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2 { x: 1600., y: 800. }),
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, 0.0, 0.0),
+                ..default()
+            },
+
             ..default()
         },
-
-        ..default()
-    },));
+        ShowScoreScreen,
+    ));
 
     commands.spawn((
         SpriteBundle {
@@ -53,26 +117,466 @@ fn setup_show_score(
         BackgroundImage,
     ));
 
-    let text_to_display = format!(
-        "your final score: {:}\ntotal mistakes: {:}",
-        score.current_score as i32, score.mistakes
-    );
-
-    commands.spawn((TextBundle::from_section(
-        text_to_display,
-        TextStyle {
-            font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
-            font_size: FINAL_SCORE_FONT_SIZE,
-            color: Color::GREEN,
-        },
-    )
-    .with_text_alignment(TextAlignment::Center)
-    .with_style(Style {
-        position_type: PositionType::Absolute,
-        left: Val::Px(500.0),
-        top: Val::Px(500.0),
-        ..default()
-    }),));
-
-    sound_event_writer.send(SoundEvent(SoundsEnum::MerryChristmas));
+    if players.is_hot_seat() {
+        spawn_scoreboard(&mut commands, &asset_server, &players, &score);
+
+        // everyone already has a name, so their scores go straight onto the table - but not
+        // a second time from a replay of the run
+        let standings = if watching_replay.is_some() {
+            Vec::new()
+        } else {
+            players.standings(&score)
+        };
+        let mut new_high_scores = 0;
+        for (name, player_score, mistakes) in standings {
+            let high_score =
+                HighScore::new(name.to_owned(), player_score as i32, mistakes, mode.clone());
+            if high_scores.add(high_score).is_some() {
+                new_high_scores += 1;
+            }
+        }
+
+        if new_high_scores > 0 {
+            high_scores.save();
+            spawn_high_score_panel(
+                &mut commands,
+                &asset_server,
+                &button_colors,
+                format!("{} new high scores!", new_high_scores),
+                false,
+            );
+        }
+    } else {
+        spawn_round_breakdown(
+            &mut commands,
+            &asset_server,
+            &score,
+            &playlist,
+            &puzzle_registry,
+            &palette,
+        );
+
+        let text_to_display = format!(
+            "your final score: {:}\ntotal mistakes: {:}",
+            score.current_score as i32, score.mistakes
+        );
+
+        commands.spawn((
+            TextBundle::from_section(
+                text_to_display,
+                TextStyle {
+                    font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                    font_size: FINAL_SCORE_FONT_SIZE,
+                    color: Color::GREEN,
+                },
+            )
+            .with_text_alignment(TextAlignment::Center)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(500.0),
+                top: Val::Px(500.0),
+                ..default()
+            }),
+            ShowScoreScreen,
+        ));
+
+        if watching_replay.is_none()
+            && high_scores
+                .place(score.current_score as i32, score.mistakes)
+                .is_some()
+        {
+            commands.insert_resource(HighScoreEntry {
+                name: String::new(),
+                mode,
+            });
+            spawn_high_score_panel(
+                &mut commands,
+                &asset_server,
+                &button_colors,
+                String::new(),
+                true,
+            );
+        }
+    }
+
+    // the buttons sit either side of the score so one kiosk can go straight into the next game
+    for (button, label, left) in [
+        (ShowScoreButton::PlayAgain, "play again", 100.0),
+        (ShowScoreButton::BackToTitle, "back to title", 1250.0),
+    ] {
+        commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(250.0),
+                        height: Val::Px(50.0),
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        left: Val::Px(left),
+                        top: Val::Px(720.0),
+                        ..Default::default()
+                    },
+                    background_color: button_colors.normal.into(),
+                    ..Default::default()
+                },
+                button,
+                ShowScoreScreen,
+            ))
+            .with_children(|parent| {
+                parent.spawn(
+                    TextBundle::from_section(
+                        label,
+                        TextStyle {
+                            font: asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf"),
+                            font_size: 50.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::Center),
+                );
+            });
+    }
+
+    sound_event_writer.send(SoundEvent::new(SoundsEnum::MerryChristmas));
+}
+
+// hot-seat - everyone's score, best first
+fn spawn_scoreboard(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    players: &Players,
+    score: &Score,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(450.0),
+                    top: Val::Px(260.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            ShowScoreScreen,
+        ))
+        .with_children(|parent| {
+            for (place, (name, player_score, mistakes)) in
+                players.standings(score).into_iter().enumerate()
+            {
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "{:}. {:}  {:}  ({:} mistakes)",
+                        place + 1,
+                        name,
+                        player_score as i32,
+                        mistakes
+                    ),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 55.0,
+                        // the winner stands out
+                        color: if place == 0 {
+                            Color::GOLD
+                        } else {
+                            Color::GREEN
+                        },
+                    },
+                ));
+            }
+        });
+}
+
+// how each round went - a row per round, above the final score
+fn spawn_round_breakdown(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    score: &Score,
+    playlist: &Playlist,
+    puzzle_registry: &PuzzleRegistry,
+    palette: &Palette,
+) {
+    const COLUMN_WIDTHS: [f32; 5] = [320.0, 140.0, 200.0, 160.0, 160.0];
+
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    let mut rows = vec![(
+        [
+            "round".to_owned(),
+            "time".to_owned(),
+            "time bonus".to_owned(),
+            "mistakes".to_owned(),
+            "net".to_owned(),
+        ],
+        Color::WHITE,
+    )];
+    for summary in score.round_summaries(playlist.puzzles()) {
+        rows.push((
+            [
+                puzzle_registry
+                    .get(summary.puzzle)
+                    .map_or(summary.puzzle.0, |stage| stage.name)
+                    .to_owned(),
+                summary
+                    .solved_in
+                    .map_or("-".to_owned(), |time| format!("{:.1}s", time.as_secs_f32())),
+                format!("{:+}", summary.time_bonus as i32),
+                summary.mistakes.to_string(),
+                format!("{:+}", summary.net_score as i32),
+            ],
+            // the rounds that cost more than they earned
+            if summary.net_score < 0.0 {
+                palette.bad()
+            } else {
+                palette.good()
+            },
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(170.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ShowScoreScreen,
+        ))
+        .with_children(|parent| {
+            for (cells, color) in rows {
+                parent.spawn(NodeBundle::default()).with_children(|parent| {
+                    for (cell, width) in cells.into_iter().zip(COLUMN_WIDTHS) {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                cell,
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 30.0,
+                                    color,
+                                },
+                            )
+                            .with_style(Style {
+                                width: Val::Px(width),
+                                ..default()
+                            }),
+                        );
+                    }
+                });
+            }
+        });
+}
+
+// across the top of the screen - the name being typed in (and a save button) or just a message
+fn spawn_high_score_panel(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    button_colors: &ButtonColors,
+    message: String,
+    with_save_button: bool,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(30.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ShowScoreScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    message,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 55.0,
+                        color: Color::GOLD,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                HighScoreText,
+            ));
+
+            if with_save_button {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(300.0),
+                                height: Val::Px(50.0),
+                                margin: UiRect::top(Val::Px(10.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: button_colors.normal.into(),
+                            ..default()
+                        },
+                        ShowScoreButton::SaveHighScore,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(
+                            TextBundle::from_section(
+                                "save score",
+                                TextStyle {
+                                    font: font.clone(),
+                                    font_size: 50.0,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_text_alignment(TextAlignment::Center),
+                        );
+                    });
+            }
+        });
+}
+
+fn click_show_score_button(
+    mut commands: Commands,
+    button_colors: Res<ButtonColors>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut playlist: ResMut<Playlist>,
+    score: Res<Score>,
+    high_score_entry: Option<Res<HighScoreEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    keys: Res<Input<KeyCode>>,
+    mut high_score_text_query: Query<&mut Text, With<HighScoreText>>,
+    save_button_query: Query<(Entity, &ShowScoreButton)>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &ShowScoreButton),
+        Changed<Interaction>,
+    >,
+) {
+    // enter does the same as the save button
+    let save_pressed = keys.just_pressed(KeyCode::Return)
+        || interaction_query.iter().any(|(interaction, _, button)| {
+            *interaction == Interaction::Pressed && matches!(button, ShowScoreButton::SaveHighScore)
+        });
+
+    if let Some(entry) = high_score_entry.filter(|_| save_pressed) {
+        let name = match entry.name.trim() {
+            "" => "the snowglobe team".to_owned(),
+            name => name.to_owned(),
+        };
+        let place = high_scores.add(HighScore::new(
+            name,
+            score.current_score as i32,
+            score.mistakes,
+            entry.mode.clone(),
+        ));
+        high_scores.save();
+
+        for mut text in high_score_text_query.iter_mut() {
+            text.sections[0].value = match place {
+                Some(place) => format!("saved - number {} on the high score table!", place + 1),
+                None => "saved".to_owned(),
+            };
+        }
+
+        // only one go at it
+        commands.remove_resource::<HighScoreEntry>();
+        for (entity, button) in save_button_query.iter() {
+            if let ShowScoreButton::SaveHighScore = button {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => match button {
+                // the same set of rounds again, straight into the first one
+                ShowScoreButton::PlayAgain => {
+                    next_game_state.set(match playlist.start() {
+                        Some(_) => GameState::Puzzle,
+                        None => GameState::TitleScreen,
+                    });
+                }
+                ShowScoreButton::BackToTitle => {
+                    next_game_state.set(GameState::TitleScreen);
+                }
+                // handled above
+                ShowScoreButton::SaveHighScore => {}
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+// typing fills in the name for the high score table
+fn type_high_score_name(
+    high_score_entry: Option<ResMut<HighScoreEntry>>,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+) {
+    let Some(mut entry) = high_score_entry else {
+        received_characters.clear();
+        return;
+    };
+
+    for ev in received_characters.iter() {
+        if !ev.char.is_control() && entry.name.chars().count() < MAX_NAME_LENGTH {
+            entry.name.push(ev.char);
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        entry.name.pop();
+    }
+}
+
+fn update_high_score_text(
+    high_score_entry: Option<Res<HighScoreEntry>>,
+    mut high_score_text_query: Query<&mut Text, With<HighScoreText>>,
+) {
+    let Some(entry) = high_score_entry else {
+        return;
+    };
+
+    for mut text in high_score_text_query.iter_mut() {
+        text.sections[0].value = format!("new high score! type your name: {}_", entry.name);
+    }
+}
+
+fn cleanup_show_score(
+    mut commands: Commands,
+    show_score_query: Query<Entity, Or<(With<ShowScoreScreen>, With<BackgroundImage>)>>,
+    mut score: ResMut<Score>,
+    mut players: ResMut<Players>,
+    mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
+) {
+    for entity in show_score_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // leaving without saving passes on the high score
+    commands.remove_resource::<HighScoreEntry>();
+
+    // whichever way we leave, the next game starts from scratch
+    *score = Score::default();
+    players.reset_scores();
+    next_puzzle_state.set(PuzzleState::GetReady);
 }
@@ -1,3 +1,4 @@
+use crate::settings::Settings;
 use crate::GameState;
 use bevy::prelude::*;
 
@@ -12,21 +13,46 @@ pub struct SnowflakesPlugin;
 
 impl Plugin for SnowflakesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::TitleScreen), setup_snowflakes)
-            .add_systems(OnEnter(GameState::ShowScore), setup_snowflakes)
-            .add_systems(
-                Update,
-                update_snowflakes.run_if(in_state(GameState::TitleScreen)),
-            )
-            .add_systems(
-                Update,
-                update_snowflakes.run_if(in_state(GameState::Instructions)),
-            )
-            .add_systems(
-                Update,
-                update_snowflakes.run_if(in_state(GameState::ShowScore)),
-            )
-            .add_systems(OnExit(GameState::Instructions), cleanup_snowflakes);
+        // coming back from the hot-seat setup (or on from loading) the snow is still falling
+        app.add_systems(
+            OnEnter(GameState::TitleScreen),
+            setup_snowflakes.run_if(not(any_with_component::<Snowflake>())),
+        )
+        .add_systems(OnEnter(GameState::ShowScore), setup_snowflakes)
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::TitleScreen)),
+        )
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::Instructions)),
+        )
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::StageSelect)),
+        )
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::PlayerSetup)),
+        )
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::Leaderboard)),
+        )
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::ShowScore)),
+        )
+        .add_systems(OnExit(GameState::Instructions), cleanup_snowflakes)
+        .add_systems(OnExit(GameState::StageSelect), cleanup_snowflakes)
+        .add_systems(OnExit(GameState::Leaderboard), cleanup_snowflakes)
+        .add_systems(OnExit(GameState::ShowScore), cleanup_snowflakes)
+        // something to watch while everything loads
+        .add_systems(OnEnter(GameState::Loading), setup_snowflakes)
+        .add_systems(
+            Update,
+            update_snowflakes.run_if(in_state(GameState::Loading)),
+        );
     }
 }
 
@@ -34,107 +60,16 @@ fn setup_snowflakes(mut commands: Commands, asset_server: Res<AssetServer>) {
     // the use of the rand library aborts is WASM so this list was created in excel using
     // the RAND() function and the copied here.
     // by accessing a modulo index below, this gives the random effect for each attribute.
-    let mut random_numbers: Vec<f32> = Vec::new();
-    random_numbers.push(0.487);
-    random_numbers.push(0.195);
-    random_numbers.push(0.544);
-    random_numbers.push(0.004);
-    random_numbers.push(0.217);
-    random_numbers.push(0.031);
-    random_numbers.push(0.466);
-    random_numbers.push(0.828);
-    random_numbers.push(0.235);
-    random_numbers.push(0.690);
-    random_numbers.push(0.903);
-    random_numbers.push(0.787);
-    random_numbers.push(0.550);
-    random_numbers.push(0.042);
-    random_numbers.push(0.603);
-    random_numbers.push(0.657);
-    random_numbers.push(0.247);
-    random_numbers.push(0.505);
-    random_numbers.push(0.237);
-    random_numbers.push(0.150);
-    random_numbers.push(0.357);
-    random_numbers.push(0.624);
-    random_numbers.push(0.611);
-    random_numbers.push(0.411);
-    random_numbers.push(0.323);
-    random_numbers.push(0.269);
-    random_numbers.push(0.574);
-    random_numbers.push(0.771);
-    random_numbers.push(0.998);
-    random_numbers.push(0.610);
-    random_numbers.push(0.735);
-    random_numbers.push(0.503);
-    random_numbers.push(0.828);
-    random_numbers.push(0.995);
-    random_numbers.push(0.104);
-    random_numbers.push(0.489);
-    random_numbers.push(0.027);
-    random_numbers.push(0.263);
-    random_numbers.push(0.681);
-    random_numbers.push(0.490);
-    random_numbers.push(0.253);
-    random_numbers.push(0.525);
-    random_numbers.push(0.102);
-    random_numbers.push(0.269);
-    random_numbers.push(0.406);
-    random_numbers.push(0.892);
-    random_numbers.push(0.326);
-    random_numbers.push(0.357);
-    random_numbers.push(0.069);
-    random_numbers.push(0.160);
-    random_numbers.push(0.462);
-    random_numbers.push(0.625);
-    random_numbers.push(0.639);
-    random_numbers.push(0.883);
-    random_numbers.push(0.975);
-    random_numbers.push(0.506);
-    random_numbers.push(0.272);
-    random_numbers.push(0.567);
-    random_numbers.push(0.462);
-    random_numbers.push(0.072);
-    random_numbers.push(0.971);
-    random_numbers.push(0.628);
-    random_numbers.push(0.043);
-    random_numbers.push(0.179);
-    random_numbers.push(0.140);
-    random_numbers.push(0.048);
-    random_numbers.push(0.485);
-    random_numbers.push(0.505);
-    random_numbers.push(0.610);
-    random_numbers.push(0.432);
-    random_numbers.push(0.024);
-    random_numbers.push(0.018);
-    random_numbers.push(0.676);
-    random_numbers.push(0.680);
-    random_numbers.push(0.721);
-    random_numbers.push(0.807);
-    random_numbers.push(0.797);
-    random_numbers.push(0.758);
-    random_numbers.push(0.050);
-    random_numbers.push(0.043);
-    random_numbers.push(0.861);
-    random_numbers.push(0.159);
-    random_numbers.push(0.162);
-    random_numbers.push(0.952);
-    random_numbers.push(0.629);
-    random_numbers.push(0.999);
-    random_numbers.push(0.782);
-    random_numbers.push(0.638);
-    random_numbers.push(0.939);
-    random_numbers.push(0.031);
-    random_numbers.push(0.579);
-    random_numbers.push(0.464);
-    random_numbers.push(0.929);
-    random_numbers.push(0.181);
-    random_numbers.push(0.479);
-    random_numbers.push(0.144);
-    random_numbers.push(0.798);
-    random_numbers.push(0.971);
-    random_numbers.push(0.948);
-    random_numbers.push(0.693);
+    let random_numbers: Vec<f32> = vec![
+        0.487, 0.195, 0.544, 0.004, 0.217, 0.031, 0.466, 0.828, 0.235, 0.690, 0.903, 0.787, 0.550,
+        0.042, 0.603, 0.657, 0.247, 0.505, 0.237, 0.150, 0.357, 0.624, 0.611, 0.411, 0.323, 0.269,
+        0.574, 0.771, 0.998, 0.610, 0.735, 0.503, 0.828, 0.995, 0.104, 0.489, 0.027, 0.263, 0.681,
+        0.490, 0.253, 0.525, 0.102, 0.269, 0.406, 0.892, 0.326, 0.357, 0.069, 0.160, 0.462, 0.625,
+        0.639, 0.883, 0.975, 0.506, 0.272, 0.567, 0.462, 0.072, 0.971, 0.628, 0.043, 0.179, 0.140,
+        0.048, 0.485, 0.505, 0.610, 0.432, 0.024, 0.018, 0.676, 0.680, 0.721, 0.807, 0.797, 0.758,
+        0.050, 0.043, 0.861, 0.159, 0.162, 0.952, 0.629, 0.999, 0.782, 0.638, 0.939, 0.031, 0.579,
+        0.464, 0.929, 0.181, 0.479, 0.144, 0.798, 0.971, 0.948, 0.693,
+    ];
 
     let mut x_positions: Vec<f32> = Vec::new();
     let mut y_positions: Vec<f32> = Vec::new();
@@ -183,7 +118,11 @@ fn setup_snowflakes(mut commands: Commands, asset_server: Res<AssetServer>) {
     }
 }
 
-fn update_snowflakes(mut transforms: Query<(&mut Transform, &Snowflake)>) {
+fn update_snowflakes(settings: Res<Settings>, mut transforms: Query<(&mut Transform, &Snowflake)>) {
+    if settings.reduced_motion {
+        return;
+    }
+
     for (mut transform, snowflake) in &mut transforms {
         transform.translation +=
             Vec3::new(snowflake.linear_x_velocity, snowflake.linear_y_velocity, 0.);
@@ -1,10 +1,15 @@
+use crate::players::Players;
+use crate::playlist::Playlist;
+use crate::puzzle::{current_stage, PuzzleRegistry};
+use crate::puzzle_definition::{PuzzleDefinition, PuzzleDefinitions};
 use crate::timer_bar::TimerResource;
 use crate::BackgroundImage;
 use crate::ButtonColors;
 use crate::GameState;
+use crate::PauseState;
 use crate::PuzzleState;
+use crate::Score;
 use crate::SoundEvent;
-use crate::SoundsEnum;
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -13,100 +18,48 @@ struct StageInstructions;
 #[derive(Component)]
 struct StageAnswers;
 
+// the begin / next puzzle button - other buttons (the pause menu) can be up at the same time
+#[derive(Component)]
+struct StageButton;
+
 const STAGE_TEXT_Z_OFFSET: f32 = 10.0;
 pub struct StageCommonPlugin;
 
 impl Plugin for StageCommonPlugin {
     fn build(&self, app: &mut App) {
-        // setup stage
-        app.add_systems(
-            OnEnter(GameState::PuzzleTurkeyMistletoe),
-            setup_stage_common,
-        )
-        .add_systems(OnEnter(GameState::PuzzlePeriodicTable), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleTwelveDays), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleReindeer), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleWaltz), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleGrocery), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleFamilyTree), setup_stage_common)
-        .add_systems(OnEnter(GameState::PuzzleCranberries), setup_stage_common)
-        // start song
-        .add_systems(OnEnter(PuzzleState::InProgress), start_song)
-        // update
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleTurkeyMistletoe)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzlePeriodicTable)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleTwelveDays)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleReindeer)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleWaltz)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleGrocery)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleFamilyTree)),
-        )
-        .add_systems(
-            Update,
-            update_stage_common.run_if(in_state(GameState::PuzzleCranberries)),
-        )
-        // puzzle complete
-        .add_systems(OnEnter(PuzzleState::Complete), puzzle_state_now_complete)
-        // cleanup stage
-        .add_systems(
-            OnExit(GameState::PuzzleTurkeyMistletoe),
-            cleanup_stage_common,
-        )
-        .add_systems(OnExit(GameState::PuzzlePeriodicTable), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleReindeer), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleWaltz), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleTwelveDays), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleGrocery), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleFamilyTree), cleanup_stage_common)
-        .add_systems(OnExit(GameState::PuzzleCranberries), cleanup_stage_common)
-        .add_systems(Update, skip_puzzle); // @rch: temporary to allow easy skips of puzzles for testing
+        app.add_systems(OnEnter(GameState::Puzzle), setup_stage_common)
+            // start song
+            .add_systems(OnEnter(PuzzleState::InProgress), start_song)
+            .add_systems(
+                Update,
+                update_stage_common
+                    .run_if(in_state(GameState::Puzzle))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            // puzzle complete
+            .add_systems(OnEnter(PuzzleState::Complete), puzzle_state_now_complete)
+            .add_systems(OnExit(GameState::Puzzle), cleanup_stage_common)
+            .add_systems(OnEnter(GameState::NextPuzzle), start_next_puzzle)
+            .add_systems(Update, skip_puzzle.run_if(in_state(PauseState::Running)));
+        // @rch: temporary to allow easy skips of puzzles for testing
     }
 }
 
 fn start_song(
     mut sound_event_writer: EventWriter<SoundEvent>,
-    current_game_state: Res<State<GameState>>,
+    playlist: Res<Playlist>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+    definition_assets: Res<Assets<PuzzleDefinition>>,
 ) {
-    match current_game_state.get() {
-        GameState::TitleScreen | GameState::Instructions | GameState::ShowScore => (),
-        GameState::PuzzleTurkeyMistletoe => {
-            sound_event_writer.send(SoundEvent(SoundsEnum::TurkeyMistletoeSong))
-        }
-        GameState::PuzzlePeriodicTable => {
-            sound_event_writer.send(SoundEvent(SoundsEnum::PeriodicTableSong))
-        }
-        GameState::PuzzleTwelveDays => {
-            sound_event_writer.send(SoundEvent(SoundsEnum::TwelveDaysSong))
-        }
-        GameState::PuzzleWaltz => sound_event_writer.send(SoundEvent(SoundsEnum::WaltzSong)),
-        GameState::PuzzleReindeer => sound_event_writer.send(SoundEvent(SoundsEnum::ReindeerSong)),
-        GameState::PuzzleGrocery => sound_event_writer.send(SoundEvent(SoundsEnum::GrocerySong)),
-        GameState::PuzzleCranberries => {
-            sound_event_writer.send(SoundEvent(SoundsEnum::CranberriesSong))
-        }
-        GameState::PuzzleFamilyTree => {
-            sound_event_writer.send(SoundEvent(SoundsEnum::FamilyTreeSong))
-        }
+    // named by the round's definition file, for the rounds that have one
+    let song = puzzle_definitions
+        .get(playlist.current(), &definition_assets)
+        .map(|definition| definition.song)
+        .or_else(|| current_stage(&playlist, &puzzle_registry).and_then(|stage| stage.song));
+
+    if let Some(song) = song {
+        sound_event_writer.send(SoundEvent::new(song));
     }
 }
 
@@ -114,25 +67,34 @@ fn setup_stage_common(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     button_colors: Res<ButtonColors>,
-    current_game_state: Res<State<GameState>>,
+    playlist: Res<Playlist>,
+    puzzle_registry: Res<PuzzleRegistry>,
 ) {
+    let Some(stage) = current_stage(&playlist, &puzzle_registry) else {
+        error!("{:?} has not been added as a puzzle", playlist.current());
+        return;
+    };
+
     commands
-        .spawn(ButtonBundle {
-            style: Style {
-                width: Val::Px(400.0),
-                height: Val::Px(50.0),
-                margin: UiRect::all(Val::Auto),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                left: Val::Px(0.0),
-                right: Val::Px(200.0),
-                top: Val::Px(325.0),
-                bottom: Val::Px(400.0),
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(400.0),
+                    height: Val::Px(50.0),
+                    margin: UiRect::all(Val::Auto),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    left: Val::Px(0.0),
+                    right: Val::Px(200.0),
+                    top: Val::Px(325.0),
+                    bottom: Val::Px(400.0),
+                    ..Default::default()
+                },
+                background_color: button_colors.normal.into(),
                 ..Default::default()
             },
-            background_color: button_colors.normal.into(),
-            ..Default::default()
-        })
+            StageButton,
+        ))
         .with_children(|parent| {
             parent.spawn(
                 TextBundle::from_section(
@@ -147,22 +109,9 @@ fn setup_stage_common(
             );
         });
 
-    let instructions_filename: &str = match current_game_state.get() {
-        GameState::PuzzleTurkeyMistletoe => "snowglobe/text/instructions - stage 1.png",
-        GameState::PuzzlePeriodicTable => "snowglobe/text/instructions - stage 2.png",
-        GameState::PuzzleTwelveDays | GameState::PuzzleReindeer => {
-            "snowglobe/text/instructions - stage 3.png"
-        }
-        GameState::PuzzleWaltz => "snowglobe/text/instructions - stage 5.png",
-        GameState::PuzzleGrocery => "snowglobe/text/instructions - stage 6.png",
-        GameState::PuzzleFamilyTree => "snowglobe/text/instructions - stage 7.png",
-        GameState::PuzzleCranberries => "snowglobe/text/instructions - stage 8.png",
-        GameState::TitleScreen | GameState::Instructions | GameState::ShowScore => "",
-    };
-
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load(instructions_filename),
+            texture: asset_server.load(stage.instructions),
             sprite: Sprite {
                 custom_size: Some(Vec2 { x: 1600., y: 800. }),
                 ..default()
@@ -178,22 +127,9 @@ fn setup_stage_common(
         StageInstructions,
     ));
 
-    let answers_filename: &str = match current_game_state.get() {
-        GameState::PuzzleTurkeyMistletoe => "snowglobe/text/answers - stage 1.png",
-        GameState::PuzzlePeriodicTable => "snowglobe/text/answers - stage 2.png",
-        GameState::PuzzleTwelveDays | GameState::PuzzleReindeer => {
-            "snowglobe/text/instructions - stage 3.png"
-        } // no answers provided
-        GameState::PuzzleWaltz => "snowglobe/text/answers - stage 5.png",
-        GameState::PuzzleGrocery => "snowglobe/text/answers - stage 6.png",
-        GameState::PuzzleFamilyTree => "snowglobe/text/answers - stage 7.png",
-        GameState::PuzzleCranberries => "snowglobe/text/answers - stage 8.png",
-        GameState::TitleScreen | GameState::Instructions | GameState::ShowScore => "",
-    };
-
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load(answers_filename),
+            texture: asset_server.load(stage.answers),
             sprite: Sprite {
                 custom_size: Some(Vec2 { x: 1600., y: 800. }),
                 ..default()
@@ -209,18 +145,6 @@ fn setup_stage_common(
         StageAnswers,
     ));
 
-    let background_image: &str = match current_game_state.get() {
-        GameState::PuzzleTurkeyMistletoe => "snowglobe/backgrounds/fireplace.png",
-        GameState::PuzzlePeriodicTable => "snowglobe/backgrounds/chemistry.png",
-        GameState::PuzzleTwelveDays => "snowglobe/backgrounds/twelve.png",
-        GameState::PuzzleReindeer => "snowglobe/backgrounds/reindeer.png",
-        GameState::PuzzleWaltz => "snowglobe/backgrounds/orchestra.png",
-        GameState::PuzzleGrocery => "snowglobe/backgrounds/grocery.png",
-        GameState::PuzzleFamilyTree => "snowglobe/backgrounds/family tree.png",
-        GameState::PuzzleCranberries => "snowglobe/backgrounds/cranberries.png",
-        GameState::TitleScreen | GameState::Instructions | GameState::ShowScore => "",
-    };
-
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
@@ -228,7 +152,7 @@ fn setup_stage_common(
                 custom_size: Some(Vec2 { x: 1600., y: 800. }),
                 ..default()
             },
-            texture: asset_server.load(background_image),
+            texture: asset_server.load(stage.background),
             visibility: Visibility::Visible,
             transform: Transform {
                 translation: Vec3::new(0.0, 0.0, -10.0),
@@ -245,13 +169,13 @@ fn update_stage_common(
     mut instructions_visibility_query: Query<(
         &mut Visibility,
         With<StageInstructions>,
-        Without<Button>,
+        Without<StageButton>,
         Without<StageAnswers>,
     )>,
     mut answers_visibility_query: Query<(
         &mut Visibility,
         With<StageAnswers>,
-        Without<Button>,
+        Without<StageButton>,
         Without<StageInstructions>,
     )>,
     timer_resource: Res<TimerResource>,
@@ -260,16 +184,21 @@ fn update_stage_common(
     mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
     button_colors: Res<ButtonColors>,
     mut next_game_state: ResMut<NextState<GameState>>,
-    current_game_state: Res<State<GameState>>,
-    mut button_visibility_query: Query<(&mut Visibility, With<Button>, Without<StageInstructions>)>,
+    playlist: Res<Playlist>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    mut button_visibility_query: Query<(
+        &mut Visibility,
+        With<StageButton>,
+        Without<StageInstructions>,
+    )>,
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<Button>),
+        (Changed<Interaction>, With<StageButton>),
     >,
 ) {
     for (interaction, mut color) in &mut interaction_query {
         match *interaction {
-            Interaction::Pressed => match current_puzzle_state.get().clone() {
+            Interaction::Pressed => match *current_puzzle_state.get() {
                 PuzzleState::GetReady => {
                     // if the button is pressed in the GetReady state, make it invisible
                     // and move the puzzle state to in progress
@@ -284,20 +213,7 @@ fn update_stage_common(
                 PuzzleState::Complete => {
                     // if the button is pressed in the Complete state, move to the next puzzle
                     next_puzzle_state.set(PuzzleState::GetReady);
-
-                    match current_game_state.get() {
-                        GameState::TitleScreen | GameState::Instructions | GameState::ShowScore => {
-                            ()
-                        }
-                        #[rustfmt::skip] GameState::PuzzleTurkeyMistletoe => next_game_state.set(GameState::PuzzlePeriodicTable),
-                        #[rustfmt::skip] GameState::PuzzlePeriodicTable   => next_game_state.set(GameState::PuzzleWaltz),
-                        #[rustfmt::skip] GameState::PuzzleWaltz           => next_game_state.set(GameState::PuzzleReindeer),
-                        #[rustfmt::skip] GameState::PuzzleReindeer        => next_game_state.set(GameState::PuzzleGrocery),
-                        #[rustfmt::skip] GameState::PuzzleGrocery         => next_game_state.set(GameState::PuzzleTwelveDays),
-                        #[rustfmt::skip] GameState::PuzzleTwelveDays      => next_game_state.set(GameState::PuzzleFamilyTree),
-                        #[rustfmt::skip] GameState::PuzzleFamilyTree      => next_game_state.set(GameState::PuzzleCranberries),
-                        #[rustfmt::skip] GameState::PuzzleCranberries     => next_game_state.set(GameState::ShowScore),
-                    }
+                    next_game_state.set(GameState::NextPuzzle);
                 }
             },
             Interaction::Hovered => {
@@ -309,19 +225,8 @@ fn update_stage_common(
         }
     }
 
-    let answer_time = match current_game_state.get() {
-        GameState::TitleScreen => 0,
-        GameState::Instructions => 0,
-        GameState::ShowScore => 0,
-        GameState::PuzzleTurkeyMistletoe => 3,
-        GameState::PuzzlePeriodicTable => 3,
-        GameState::PuzzleTwelveDays => 3,
-        GameState::PuzzleReindeer => 3,
-        GameState::PuzzleWaltz => 3,
-        GameState::PuzzleGrocery => 3,
-        GameState::PuzzleFamilyTree => 5,
-        GameState::PuzzleCranberries => 4,
-    };
+    let answer_time =
+        current_stage(&playlist, &puzzle_registry).map_or(0, |stage| stage.answer_reveal_seconds);
 
     if (timer_resource.time_remaining.duration() - timer_resource.time_remaining.elapsed())
         .as_secs()
@@ -336,11 +241,12 @@ fn update_stage_common(
 }
 
 fn puzzle_state_now_complete(
-    mut visibility_query: Query<(&mut Visibility, &Button)>,
+    mut visibility_query: Query<(&mut Visibility, &StageButton)>,
 
-    mut non_interaction_query: Query<&Children, With<Button>>,
+    mut non_interaction_query: Query<&Children, With<StageButton>>,
     mut button_text_query: Query<&mut Text>,
-    current_game_state: Res<State<GameState>>,
+    playlist: Res<Playlist>,
+    players: Res<Players>,
 ) {
     // make the button visible again
     let mut button_visibility = visibility_query.single_mut();
@@ -348,22 +254,45 @@ fn puzzle_state_now_complete(
 
     for children in &mut non_interaction_query {
         let mut text = button_text_query.get_mut(children[0]).unwrap();
-        match current_game_state.get() {
-            GameState::PuzzleCranberries => text.sections[0].value = "show final score".to_string(),
-            _ => text.sections[0].value = "go to next puzzle!".to_string(),
-        }
+        // the same as start_next_puzzle - everyone has a go at a round before moving on
+        text.sections[0].value = if !players.is_last_turn() {
+            "next player's turn".to_string()
+        } else if playlist.is_last() {
+            "show final score".to_string()
+        } else {
+            "go to next puzzle!".to_string()
+        };
+    }
+}
+
+fn start_next_puzzle(
+    mut playlist: ResMut<Playlist>,
+    mut players: ResMut<Players>,
+    mut score: ResMut<Score>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    // in a hot-seat game everyone has a go at a round before moving on
+    if players.next_turn(&mut score) {
+        next_game_state.set(GameState::Puzzle);
+        return;
     }
+
+    // the round that just finished has been cleaned up - on to the next one, or the score
+    next_game_state.set(match playlist.advance() {
+        Some(_) => GameState::Puzzle,
+        None => GameState::ShowScore,
+    });
 }
 
 fn cleanup_stage_common(
     mut commands: Commands,
-    button_query: Query<Entity, With<Button>>,
+    button_query: Query<Entity, With<StageButton>>,
     background_image_query: Query<Entity, With<BackgroundImage>>,
     stage_instructions_query: Query<Entity, With<StageInstructions>>,
     stage_answers_query: Query<Entity, With<StageAnswers>>,
 ) {
     for button in button_query.iter() {
-        commands.entity(button).despawn();
+        commands.entity(button).despawn_recursive();
     }
 
     for background_image in background_image_query.iter() {
@@ -385,9 +314,47 @@ fn skip_puzzle(
     mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
     keys: Res<Input<KeyCode>>,
 ) {
-    if keys.just_pressed(KeyCode::S) {
-        if *current_puzzle_state.get() == PuzzleState::InProgress {
-            next_puzzle_state.set(PuzzleState::Complete)
-        }
+    if keys.just_pressed(KeyCode::S) && *current_puzzle_state.get() == PuzzleState::InProgress {
+        next_puzzle_state.set(PuzzleState::Complete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_definition::REINDEER;
+    use crate::testing::TestGame;
+
+    #[test]
+    fn everyone_gets_a_turn_at_the_last_round() {
+        let mut game = TestGame::new();
+        game.click_button("stage select");
+        *game.world_mut().resource_mut::<Players>() =
+            Players::named(&["holly".to_string(), "ivy".to_string()]);
+        let name = game
+            .world()
+            .resource::<PuzzleRegistry>()
+            .get(REINDEER)
+            .unwrap()
+            .name;
+        game.click_button(name);
+        game.click_button("click to begin puzzle");
+
+        game.run_out_the_clock();
+        assert_eq!(game.puzzle_state(), PuzzleState::Complete);
+        assert!(game.find_button("show final score").is_none());
+        game.click_button("next player's turn");
+        game.update();
+        assert_eq!(game.game_state(), GameState::Puzzle);
+        assert_eq!(
+            game.world().resource::<Players>().current_name(),
+            Some("ivy")
+        );
+
+        game.click_button("click to begin puzzle");
+        game.run_out_the_clock();
+        game.click_button("show final score");
+        game.update();
+        assert_eq!(game.game_state(), GameState::ShowScore);
     }
 }
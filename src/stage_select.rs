@@ -0,0 +1,218 @@
+use crate::picking::{LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::playlist::Playlist;
+use crate::puzzle::{PuzzleId, PuzzleRegistry};
+use crate::BackgroundImage;
+use crate::ButtonColors;
+use crate::GameState;
+use crate::Score;
+use bevy::prelude::*;
+
+const THUMBNAIL_SIZE: Vec2 = Vec2::new(300., 150.);
+
+#[derive(Component)]
+struct StageSelectMenu;
+
+#[derive(Component)]
+enum StageSelectButton {
+    Puzzle(PuzzleId),
+    Back,
+}
+
+pub struct StageSelectPlugin;
+
+/// Lists every round so a single puzzle can be played on its own
+impl Plugin for StageSelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::StageSelect), setup_stage_select)
+            .add_systems(
+                Update,
+                click_stage_select_button.run_if(in_state(GameState::StageSelect)),
+            )
+            .add_systems(OnExit(GameState::StageSelect), cleanup_stage_select);
+    }
+}
+
+fn setup_stage_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    button_colors: Res<ButtonColors>,
+    puzzle_registry: Res<PuzzleRegistry>,
+) {
+    let font = asset_server.load("snowglobe/fonts/MTF Dear Santa.ttf");
+
+    // the rounds of the full game in their usual order, then anything else that has been added
+    let full_game = Playlist::full();
+    let mut puzzles: Vec<PuzzleId> = full_game.puzzles().to_vec();
+    for (id, _) in puzzle_registry.iter() {
+        if !puzzles.contains(id) {
+            puzzles.push(*id);
+        }
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(LAYOUT_WIDTH),
+                    height: Val::Px(LAYOUT_HEIGHT),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            StageSelectMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "pick a puzzle",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 80.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+
+            // the thumbnails wrap onto as many rows as they need
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(1400.0),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for id in puzzles.iter() {
+                        let Some(stage) = puzzle_registry.get(*id) else {
+                            continue;
+                        };
+
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        flex_direction: FlexDirection::Column,
+                                        align_items: AlignItems::Center,
+                                        padding: UiRect::all(Val::Px(5.0)),
+                                        margin: UiRect::all(Val::Px(10.0)),
+                                        ..default()
+                                    },
+                                    background_color: button_colors.normal.into(),
+                                    ..default()
+                                },
+                                StageSelectButton::Puzzle(*id),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(ImageBundle {
+                                    style: Style {
+                                        width: Val::Px(THUMBNAIL_SIZE.x),
+                                        height: Val::Px(THUMBNAIL_SIZE.y),
+                                        ..default()
+                                    },
+                                    image: asset_server.load(stage.background).into(),
+                                    ..default()
+                                });
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        stage.name,
+                                        TextStyle {
+                                            font: font.clone(),
+                                            font_size: 40.0,
+                                            color: Color::WHITE,
+                                        },
+                                    )
+                                    .with_text_alignment(TextAlignment::Center),
+                                );
+                            });
+                    }
+                });
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(300.0),
+                            height: Val::Px(50.0),
+                            margin: UiRect::top(Val::Px(20.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: button_colors.normal.into(),
+                        ..default()
+                    },
+                    StageSelectButton::Back,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "back",
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 60.0,
+                                color: Color::WHITE,
+                            },
+                        )
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+                });
+        });
+}
+
+fn click_stage_select_button(
+    button_colors: Res<ButtonColors>,
+    mut state: ResMut<NextState<GameState>>,
+    mut playlist: ResMut<Playlist>,
+    mut score: ResMut<Score>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &StageSelectButton),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => match button {
+                StageSelectButton::Puzzle(id) => {
+                    // a practice round - just this one puzzle, starting from a clean score
+                    let name = puzzle_registry.get(*id).map_or(id.0, |stage| stage.name);
+                    *playlist = Playlist::new(name, vec![*id]);
+                    playlist.start();
+                    *score = Score::default();
+                    state.set(GameState::Puzzle);
+                }
+                StageSelectButton::Back => {
+                    state.set(GameState::TitleScreen);
+                }
+            },
+            Interaction::Hovered => {
+                *color = button_colors.hovered.into();
+            }
+            Interaction::None => {
+                *color = button_colors.normal.into();
+            }
+        }
+    }
+}
+
+fn cleanup_stage_select(
+    mut commands: Commands,
+    menu: Query<Entity, With<StageSelectMenu>>,
+    background_image: Query<Entity, With<BackgroundImage>>,
+) {
+    for entity in menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // the title screen background was left up for this menu
+    for entity in background_image.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
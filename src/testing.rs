@@ -0,0 +1,379 @@
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::MouseButtonInput;
+use bevy::input::touch::{TouchInput, TouchPhase};
+use bevy::input::{ButtonState, InputPlugin};
+use bevy::prelude::*;
+use bevy::render::camera::CameraPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::PrimaryWindow;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::drag_and_drop::{DragAndDropParameters, SpriteData};
+use crate::picking::{Letterbox, LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::puzzle::{PuzzleId, PuzzleRegistry};
+use crate::puzzle_definition::TileTarget;
+use crate::replay::REPLAY_STEP;
+use crate::timer_bar::TimerResource;
+use crate::{GameState, PuzzleState, Score, ScoreReason, SnowglobePlugin};
+
+/// How far the clock moves each update (the same as in a run that is being recorded) - it only
+/// moves when the game is updated, so the tests don't depend on how quick the machine is
+pub const FRAME: Duration = REPLAY_STEP;
+
+// the asset files are read on other threads in real time, so this is the one thing waited for
+const LOADING_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The game without a window, a renderer or sound, driven one update at a time with a fake
+/// mouse. Starts out on the title screen
+pub struct TestGame {
+    app: App,
+}
+
+impl TestGame {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            // a window the size of the layout, so window and layout coordinates are the same
+            WindowPlugin {
+                primary_window: Some(Window {
+                    resolution: (LAYOUT_WIDTH, LAYOUT_HEIGHT).into(),
+                    ..default()
+                }),
+                ..default()
+            },
+            InputPlugin,
+            TransformPlugin,
+            HierarchyPlugin,
+            // just the camera bookkeeping, for finding the pointer in the world
+            CameraPlugin,
+        ))
+        // normally added by the sprite, ui and render plugins, which need a gpu
+        .add_asset::<Image>()
+        .add_asset::<Mesh>()
+        .add_asset::<ColorMaterial>()
+        .init_resource::<UiScale>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+        .add_plugins(SnowglobePlugin);
+        app.finish();
+        app.cleanup();
+
+        let mut game = TestGame { app };
+        game.finish_loading();
+        game
+    }
+
+    // there are no loaders for the pictures, fonts or sounds without the other plugins, so
+    // the loading screen ends up listing them - and is told to carry on
+    fn finish_loading(&mut self) {
+        let started = Instant::now();
+        while self.game_state() == GameState::Loading {
+            assert!(
+                started.elapsed() < LOADING_TIMEOUT,
+                "still loading after {:?}",
+                LOADING_TIMEOUT
+            );
+
+            if self.find_button("continue anyway").is_some() {
+                self.click_button("continue anyway");
+            } else {
+                self.update();
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+        assert_eq!(self.game_state(), GameState::TitleScreen);
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    /// Keeps updating until that much game time has gone by
+    pub fn run_for(&mut self, duration: Duration) {
+        let frames = (duration.as_secs_f64() / FRAME.as_secs_f64()).ceil() as u32;
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    /// Skips ahead to the round's timer running out (and the round ending) - in one step, as
+    /// there is nothing to watch on the way
+    pub fn run_out_the_clock(&mut self) {
+        let remaining = self
+            .app
+            .world
+            .resource::<TimerResource>()
+            .time_remaining
+            .remaining();
+
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(remaining));
+        self.update();
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+        self.run_for(FRAME * 2);
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    pub fn game_state(&self) -> GameState {
+        *self.app.world.resource::<State<GameState>>().get()
+    }
+
+    pub fn puzzle_state(&self) -> PuzzleState {
+        *self.app.world.resource::<State<PuzzleState>>().get()
+    }
+
+    /// Every change to the score so far, oldest first
+    pub fn score_changes(&self) -> Vec<(ScoreReason, f32)> {
+        self.app
+            .world
+            .resource::<Score>()
+            .history
+            .iter()
+            .map(|change| (change.reason, change.delta_score))
+            .collect()
+    }
+
+    /// A button that is showing, found by its text
+    pub fn find_button(&mut self, label: &str) -> Option<Entity> {
+        let mut buttons = self
+            .app
+            .world
+            .query_filtered::<(Entity, &Children, &Visibility), With<Button>>();
+        let world = &self.app.world;
+
+        buttons
+            .iter(world)
+            .filter(|(_, _, visibility)| **visibility != Visibility::Hidden)
+            .find(|(_, children, _)| {
+                children.iter().any(|child| {
+                    world.get::<Text>(*child).is_some_and(|text| {
+                        text.sections.iter().any(|section| section.value == label)
+                    })
+                })
+            })
+            .map(|(entity, _, _)| entity)
+    }
+
+    /// Presses and lets go of a button the way bevy's ui would, without working out where it
+    /// is - there is no ui layout without the renderer
+    pub fn click_button(&mut self, label: &str) {
+        let button = self
+            .find_button(label)
+            .unwrap_or_else(|| panic!("no {:?} button showing", label));
+
+        self.app
+            .world
+            .entity_mut(button)
+            .insert(Interaction::Pressed);
+        self.update();
+        // it could have been despawned by what it did
+        if let Some(mut interaction) = self.app.world.get_mut::<Interaction>(button) {
+            *interaction = Interaction::None;
+        }
+        self.update();
+    }
+
+    fn window(&mut self) -> Entity {
+        self.app
+            .world
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(&self.app.world)
+    }
+
+    // from a point in the world (the middle of the layout is the origin, y up)
+    fn world_to_window(&self, world_position: Vec2) -> Vec2 {
+        let layout_position = Vec2::new(
+            world_position.x + LAYOUT_WIDTH / 2.,
+            LAYOUT_HEIGHT / 2. - world_position.y,
+        );
+        self.app
+            .world
+            .resource::<Letterbox>()
+            .layout_to_window(layout_position)
+    }
+
+    /// Moves the mouse to a point in the world (the middle of the layout is the origin, y up)
+    pub fn move_mouse(&mut self, world_position: Vec2) {
+        let position = self.world_to_window(world_position);
+        let window = self.window();
+
+        self.app.world.send_event(CursorMoved { window, position });
+        self.update();
+    }
+
+    fn mouse_button(&mut self, state: ButtonState) {
+        let window = self.window();
+
+        self.app.world.send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        });
+        self.update();
+    }
+
+    pub fn press(&mut self) {
+        self.mouse_button(ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self) {
+        self.mouse_button(ButtonState::Released);
+    }
+
+    pub fn click(&mut self, world_position: Vec2) {
+        self.move_mouse(world_position);
+        self.press();
+        self.release();
+    }
+
+    pub fn drag(&mut self, from: Vec2, to: Vec2) {
+        self.move_mouse(from);
+        self.press();
+        self.move_mouse(to);
+        self.release();
+    }
+
+    /// Presses a button with the mouse, which is taken to be over it - the press and the release
+    /// are real mouse events, but the button is pressed by hand as there is no ui layout
+    pub fn click_button_with_mouse(&mut self, label: &str) {
+        let button = self
+            .find_button(label)
+            .unwrap_or_else(|| panic!("no {:?} button showing", label));
+        let window = self.window();
+
+        self.app.world.send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state: ButtonState::Pressed,
+            window,
+        });
+        self.app
+            .world
+            .entity_mut(button)
+            .insert(Interaction::Pressed);
+        self.update();
+        if let Some(mut interaction) = self.app.world.get_mut::<Interaction>(button) {
+            *interaction = Interaction::Hovered;
+        }
+        self.release();
+    }
+
+    // the same finger every time - only the first finger down is the pointer
+    fn touch(&mut self, phase: TouchPhase, world_position: Vec2) {
+        let position = self.world_to_window(world_position);
+
+        self.app.world.send_event(TouchInput {
+            phase,
+            position,
+            force: None,
+            id: 0,
+        });
+    }
+
+    /// Puts a finger on the screen at a point in the world
+    pub fn touch_down(&mut self, world_position: Vec2) {
+        self.touch(TouchPhase::Started, world_position);
+        self.update();
+    }
+
+    pub fn touch_move(&mut self, world_position: Vec2) {
+        self.touch(TouchPhase::Moved, world_position);
+        self.update();
+    }
+
+    pub fn touch_up(&mut self, world_position: Vec2) {
+        self.touch(TouchPhase::Ended, world_position);
+        self.update();
+    }
+
+    /// The system losing track of the finger, rather than it being lifted
+    pub fn touch_cancel(&mut self, world_position: Vec2) {
+        self.touch(TouchPhase::Canceled, world_position);
+        self.update();
+    }
+
+    /// A finger going down and up again between two frames
+    pub fn tap(&mut self, world_position: Vec2) {
+        self.touch(TouchPhase::Started, world_position);
+        self.touch(TouchPhase::Ended, world_position);
+        self.update();
+    }
+
+    pub fn touch_drag(&mut self, from: Vec2, to: Vec2) {
+        self.touch_down(from);
+        self.touch_move((from + to) / 2.0);
+        self.touch_move(to);
+        self.touch_up(to);
+    }
+
+    /// Presses a key and lets go of it again
+    pub fn press_key(&mut self, key_code: KeyCode) {
+        let window = self.window();
+
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            self.app.world.send_event(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key_code),
+                state,
+                window,
+            });
+            self.update();
+        }
+    }
+
+    /// Picks one round from the stage select screen and clicks the button to start it
+    pub fn play_round(&mut self, puzzle: PuzzleId) {
+        let name = self
+            .app
+            .world
+            .resource::<PuzzleRegistry>()
+            .get(puzzle)
+            .unwrap_or_else(|| panic!("{:?} isn't registered", puzzle))
+            .name;
+
+        self.click_button("stage select");
+        assert_eq!(self.game_state(), GameState::StageSelect);
+        self.click_button(name);
+        assert_eq!(self.game_state(), GameState::Puzzle);
+        assert_eq!(self.puzzle_state(), PuzzleState::GetReady);
+        self.click_button("click to begin puzzle");
+        assert_eq!(self.puzzle_state(), PuzzleState::InProgress);
+    }
+
+    /// Where to pick up and drop each tile the drag and drop round needs placed - one tile to
+    /// each slot, and tiles that can go anywhere just moved down out of the line
+    pub fn solution(&mut self) -> Vec<(Vec2, Vec2)> {
+        let required_placements = self
+            .app
+            .world
+            .resource::<DragAndDropParameters>()
+            .required_placements;
+        let mut filled_slots = Vec::new();
+        let mut tiles = self.app.world.query::<&SpriteData>();
+
+        tiles
+            .iter(&self.app.world)
+            .filter_map(|tile| {
+                let from = tile.starting_location.truncate();
+                match tile.target {
+                    TileTarget::Slot(slot) if !filled_slots.contains(&slot) => {
+                        filled_slots.push(slot);
+                        Some((from, tile.correct_location.truncate()))
+                    }
+                    TileTarget::Anywhere => Some((from, from - Vec2::new(0.0, 150.0))),
+                    _ => None,
+                }
+            })
+            .take(required_placements)
+            .collect()
+    }
+}
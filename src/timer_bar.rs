@@ -2,7 +2,12 @@ use bevy::prelude::*;
 use bevy::sprite::MaterialMesh2dBundle;
 use std::time::Duration;
 
+use crate::difficulty::Difficulty;
+use crate::playlist::Playlist;
+use crate::puzzle::{current_stage, PuzzleRegistry};
+use crate::puzzle_definition::{PuzzleDefinition, PuzzleDefinitions};
 use crate::GameState;
+use crate::PauseState;
 use crate::PuzzleState;
 
 #[derive(Component)]
@@ -25,36 +30,15 @@ pub struct TimerBarPlugin;
 
 impl Plugin for TimerBarPlugin {
     fn build(&self, app: &mut App) {
-        // setup - @rch: fix all these setups
-        app.add_systems(OnEnter(GameState::PuzzleTurkeyMistletoe), setup)
-            .add_systems(OnEnter(GameState::PuzzlePeriodicTable), setup)
-            .add_systems(OnEnter(GameState::PuzzleTwelveDays), setup)
-            .add_systems(OnEnter(GameState::PuzzleReindeer), setup)
-            .add_systems(OnEnter(GameState::PuzzleWaltz), setup)
-            .add_systems(OnEnter(GameState::PuzzleGrocery), setup)
-            .add_systems(OnEnter(GameState::PuzzleFamilyTree), setup)
-            .add_systems(OnEnter(GameState::PuzzleCranberries), setup)
-            // update
+        app.add_systems(OnEnter(GameState::Puzzle), setup)
             .add_systems(
                 Update,
-                update.run_if(in_state(GameState::PuzzleTurkeyMistletoe)),
+                update
+                    .run_if(in_state(GameState::Puzzle))
+                    .run_if(in_state(PauseState::Running)),
             )
-            .add_systems(
-                Update,
-                update.run_if(in_state(GameState::PuzzlePeriodicTable)),
-            )
-            .add_systems(Update, update.run_if(in_state(GameState::PuzzleTwelveDays)))
-            .add_systems(Update, update.run_if(in_state(GameState::PuzzleReindeer)))
-            .add_systems(Update, update.run_if(in_state(GameState::PuzzleWaltz)))
-            .add_systems(Update, update.run_if(in_state(GameState::PuzzleGrocery)))
-            .add_systems(Update, update.run_if(in_state(GameState::PuzzleFamilyTree)))
-            .add_systems(
-                Update,
-                update.run_if(in_state(GameState::PuzzleCranberries)),
-            )
-            // cleanup
-            .add_systems(OnExit(PuzzleState::Complete), cleanup);
-        //.add_systems(OnExit(GameState::PuzzleCranberries), cleanup);
+            // cleanup - also when a round is restarted or abandoned from the pause menu
+            .add_systems(OnExit(GameState::Puzzle), cleanup);
     }
 }
 
@@ -63,7 +47,11 @@ fn setup(
     asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    current_game_state: Res<State<GameState>>,
+    playlist: Res<Playlist>,
+    puzzle_registry: Res<PuzzleRegistry>,
+    puzzle_definitions: Res<PuzzleDefinitions>,
+    definition_assets: Res<Assets<PuzzleDefinition>>,
+    difficulty: Res<Difficulty>,
 ) {
     // timer background
     let timer_background_id = commands
@@ -120,19 +108,17 @@ fn setup(
         TimeRemainingText,
     ));
 
-    let total_time: u64 = match current_game_state.get() {
-        GameState::Instructions | GameState::TitleScreen | GameState::ShowScore => 0,
-        GameState::PuzzlePeriodicTable
-        | GameState::PuzzleTurkeyMistletoe
-        | GameState::PuzzleGrocery
-        | GameState::PuzzleCranberries
-        | GameState::PuzzleWaltz => 20,
-        GameState::PuzzleFamilyTree => 27,
-        GameState::PuzzleTwelveDays | GameState::PuzzleReindeer => 60,
-    };
+    // from the round's definition file, for the rounds that have one
+    let total_time: u64 = puzzle_definitions
+        .get(playlist.current(), &definition_assets)
+        .map(|definition| definition.timer_seconds)
+        .or_else(|| {
+            current_stage(&playlist, &puzzle_registry).and_then(|stage| stage.timer_seconds)
+        })
+        .unwrap_or(0);
 
     commands.insert_resource(TimerResource {
-        time_remaining: Timer::new(Duration::new(total_time, 0), TimerMode::Once),
+        time_remaining: Timer::new(difficulty.timer(total_time), TimerMode::Once),
     });
 }
 
@@ -145,15 +131,14 @@ fn update(
     mut next_puzzle_state: ResMut<NextState<PuzzleState>>,
 ) {
     // decrement the remaining time - if the puzzle is in progress
-    if puzzle_state.get().clone() == PuzzleState::InProgress {
+    if *puzzle_state.get() == PuzzleState::InProgress {
         timer_resource.time_remaining.tick(time.delta());
     }
 
     let time_remaining =
         timer_resource.time_remaining.duration() - timer_resource.time_remaining.elapsed();
 
-    // the first format! makes a string - the second one removes the quotes
-    let time_remaining_string: String = format!("{}", format!("{:?}", time_remaining));
+    let time_remaining_string: String = format!("{:?}", time_remaining);
 
     let time_remaining_truncated: &str;
     let mut leading_zero = "0.".to_owned(); // has to be declared out here so it 'lives long enough'
@@ -174,7 +159,7 @@ fn update(
         if time_remaining > Duration::ZERO {
             text.sections[0].value = format!("time remaining: {:}", time_remaining_truncated);
         } else {
-            text.sections[0].value = format!("time remaining: 0.0");
+            text.sections[0].value = "time remaining: 0.0".to_string();
         }
     }
 
@@ -191,13 +176,13 @@ fn update(
         timer_bar.previous_scale = scale_percentage;
     }
 
-    if time_remaining <= Duration::ZERO && puzzle_state.get().clone() == PuzzleState::InProgress {
+    if time_remaining <= Duration::ZERO && *puzzle_state.get() == PuzzleState::InProgress {
         next_puzzle_state.set(PuzzleState::Complete);
     }
 }
 
 // helper function for the time remaining string (copied from somewhere)
-fn truncate(s: &String, max_chars: usize) -> &str {
+fn truncate(s: &str, max_chars: usize) -> &str {
     match s.char_indices().nth(max_chars) {
         None => s,
         Some((idx, _)) => &s[..idx],
@@ -206,19 +191,10 @@ fn truncate(s: &String, max_chars: usize) -> &str {
 
 fn cleanup(
     mut commands: Commands,
-    button: Query<Entity, With<Button>>,
-    time_remaining_text: Query<Entity, With<TimeRemainingText>>,
-    timer_bar_parent_query: Query<Entity, With<TimerBarParent>>,
-    timer_bar_query: Query<Entity, With<TimerBar>>,
+    // the red bar is a child of the background bar (the one with the TimerBar component)
+    timer_entity_query: Query<Entity, Or<(With<TimeRemainingText>, With<TimerBar>)>>,
 ) {
-    commands.entity(button.single()).despawn_recursive();
-    commands.entity(time_remaining_text.single()).despawn();
-    commands
-        .entity(timer_bar_parent_query.single())
-        .despawn_recursive();
-    commands
-        .entity(timer_bar_query.single())
-        .despawn_recursive();
-
-    //commands.remove_resource::<TimerResource>();
+    for entity in timer_entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use crate::PauseState;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    // quick at first, then slowing down as it gets there
+    OutCubic,
+    InOutCubic,
+    // goes a little past the end and settles back
+    OutBack,
+}
+
+impl Easing {
+    /// How far along the curve is, for t from 0 to 1
+    pub fn ease(self, t: f32) -> f32 {
+        match self {
+            Easing::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::InOutCubic => {
+                if t < 0.5 {
+                    4.0 * t.powi(3)
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::OutBack => {
+                const OVERSHOOT: f32 = 1.70158;
+                1.0 + (OVERSHOOT + 1.0) * (t - 1.0).powi(3) + OVERSHOOT * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+/// Moves an entity's Transform from one place to another over time, then removes itself
+#[derive(Component, Debug, Clone)]
+pub struct Tween {
+    from: Transform,
+    to: Transform,
+    timer: Timer,
+    easing: Easing,
+    // side to side, dying away as it goes
+    shake: f32,
+}
+
+// how many times a shake goes side to side
+const SHAKES: f32 = 3.;
+
+impl Tween {
+    pub fn new(from: Transform, to: Transform, duration: Duration, easing: Easing) -> Self {
+        Tween {
+            from,
+            to,
+            timer: Timer::new(duration, TimerMode::Once),
+            easing,
+            shake: 0.0,
+        }
+    }
+
+    pub fn with_shake(mut self, amplitude: f32) -> Self {
+        self.shake = amplitude;
+        self
+    }
+
+    fn transform(&self) -> Transform {
+        let t = self.timer.percent();
+        let eased = self.easing.ease(t);
+        let shake = self.shake * (t * SHAKES * TAU).sin() * (1.0 - t);
+
+        Transform {
+            translation: self.from.translation.lerp(self.to.translation, eased)
+                + Vec3::new(shake, 0.0, 0.0),
+            rotation: self.from.rotation.slerp(self.to.rotation, eased),
+            scale: self.from.scale.lerp(self.to.scale, eased),
+        }
+    }
+}
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_tweens.run_if(in_state(PauseState::Running)));
+    }
+}
+
+fn update_tweens(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tweens: Query<(Entity, &mut Tween, &mut Transform)>,
+) {
+    for (entity, mut tween, mut transform) in tweens.iter_mut() {
+        tween.timer.tick(time.delta());
+
+        if tween.timer.finished() {
+            *transform = tween.to;
+            commands.entity(entity).remove::<Tween>();
+        } else {
+            *transform = tween.transform();
+        }
+    }
+}